@@ -1,3 +1,4 @@
+use std::env;
 use std::fs;
 use std::path::Path;
 use std::collections::HashMap;
@@ -84,17 +85,240 @@ fn parse_cldr_keywords(path: &str) -> HashMap<String, (String, Vec<String>)> {
     map
 }
 
+/// Locales compiled in when `EMOJI_PICKER_LOCALES` is not set.
+const DEFAULT_LOCALES: &str = "en,nl";
+
+/// Directories of the CLDR annotations, watched so a newly downloaded locale or derived file
+/// also triggers a rebuild.
+const ANNOTATION_DIRS: &[&str] = &["data/downloaded/common/annotations", "data/downloaded/common/annotationsDerived"];
+
+/// The CLDR locales to compile into `EMOJIS`, from the comma-separated `EMOJI_PICKER_LOCALES`
+/// (e.g. `en,nl,de,fr`). English always comes first: the names in emoji-test.txt are English
+/// and it is the fallback for every other locale.
+fn configured_locales() -> Vec<String> {
+    println!("cargo:rerun-if-env-changed=EMOJI_PICKER_LOCALES");
+    let configured = std::env::var("EMOJI_PICKER_LOCALES").unwrap_or_else(|_| DEFAULT_LOCALES.to_string());
+    let mut locales = vec!["en".to_string()];
+    for locale in configured.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+        if !locales.iter().any(|l| l == locale) {
+            locales.push(locale.to_string());
+        }
+    }
+    locales
+}
+
+/// CLDR annotations of one locale: the main file and the (optional) derived annotations.
+struct LocaleAnnotations {
+    locale: String,
+    main: HashMap<String, (String, Vec<String>)>,
+    derived: HashMap<String, (String, Vec<String>)>,
+}
+
+fn load_locale(locale: &str) -> LocaleAnnotations {
+    let main = format!("data/downloaded/common/annotations/{}.xml", locale);
+    let derived = format!("data/downloaded/common/annotationsDerived/{}.xml", locale);
+    if !Path::new(&main).exists() {
+        panic!("CLDR annotations for locale {:?} not found at {}", locale, main);
+    }
+    for path in [&main, &derived].into_iter().filter(|path| Path::new(path).exists()) {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+    LocaleAnnotations {
+        locale: locale.to_string(),
+        main: parse_cldr_keywords(&main),
+        derived: if Path::new(&derived).exists() { parse_cldr_keywords(&derived) } else { HashMap::new() },
+    }
+}
+
+/// Bit set of the characters in `text`: one bit per ASCII letter, the rest hashed into the
+/// remaining six bits. Must match `index::letter_mask` in the crate.
+fn letter_mask(text: &str) -> u32 {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .fold(0, |mask, c| {
+            let bit = if c.is_ascii_lowercase() { c as u32 - 'a' as u32 } else { 26 + c as u32 % 6 };
+            mask | 1 << bit
+        })
+}
+
+/// Generate the precomputed search index for `EMOJIS`, included by `src/emoji/search_index.rs`.
+///
+/// `entries` holds the name and keywords of each emoji per locale (in `LOCALES` order), in
+/// `EMOJIS` order. The output has the lowercased texts per emoji (so search never has to
+/// normalize at runtime) and a sorted table of every distinct word, in any locale, with the
+/// indices of the emoji using it, for prefix lookups.
+fn generate_search_index(entries: &[Vec<(String, Vec<String>)>]) -> String {
+    let mut out = String::from("// This file is @generated by build.rs\n\n");
+    out.push_str("/// Normalized (lowercase) name and keywords of an emoji in one locale.\n");
+    out.push_str("pub struct LocalizedTexts {\n    pub name: &'static str,\n    pub keywords: &'static [&'static str],\n}\n\n");
+    out.push_str("/// Normalized search texts of one emoji, parallel to `EMOJIS`.\n");
+    out.push_str("pub struct SearchTexts {\n    /// Texts per locale, parallel to `LOCALES`.\n    pub localized: &'static [LocalizedTexts],\n");
+    out.push_str("    /// Bit set of the letters used in all texts (see `index::letter_mask`).\n    pub letters: u32,\n}\n\n");
+    out.push_str("pub static SEARCH_TEXTS: &[SearchTexts] = &[\n");
+    let mut tokens: std::collections::BTreeMap<String, Vec<usize>> = std::collections::BTreeMap::new();
+    for (i, localized) in entries.iter().enumerate() {
+        let localized: Vec<(String, Vec<String>)> = localized
+            .iter()
+            .map(|(name, keywords)| (name.to_lowercase(), keywords.iter().map(|k| k.to_lowercase()).collect()))
+            .collect();
+        let texts = || localized.iter().flat_map(|(name, keywords)| std::iter::once(name).chain(keywords));
+        for word in texts().flat_map(|t| index_words(t)) {
+            let postings = tokens.entry(word.to_string()).or_default();
+            if postings.last() != Some(&i) {
+                postings.push(i);
+            }
+        }
+        let letters = texts().fold(0, |mask, t| mask | letter_mask(t));
+        let localized: Vec<String> = localized
+            .iter()
+            .map(|(name, keywords)| {
+                let keywords: Vec<String> = keywords.iter().map(|k| format!("{:?}", k)).collect();
+                format!("LocalizedTexts {{ name: {:?}, keywords: &[{}] }}", name, keywords.join(", "))
+            })
+            .collect();
+        out.push_str(&format!(
+            "    SearchTexts {{ localized: &[{}], letters: {:#x} }},\n",
+            localized.join(", "),
+            letters
+        ));
+    }
+    out.push_str("];\n\n");
+    out.push_str("/// Every distinct word in `SEARCH_TEXTS`, sorted, with the indices into `EMOJIS` that use it.\n");
+    out.push_str("pub static TOKENS: &[(&str, &[u16])] = &[\n");
+    for (token, postings) in &tokens {
+        let postings: Vec<String> = postings.iter().map(|i| i.to_string()).collect();
+        out.push_str(&format!("    ({:?}, &[{}]),\n", token, postings.join(", ")));
+    }
+    out.push_str("];\n");
+    out
+}
+
+/// The words of `text` in the token table: each whitespace-separated word, and the parts of
+/// hyphenated words, as fuzzy matching starts words after a hyphen too. Must match
+/// `index::index_words` in the crate.
+fn index_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .flat_map(|word| std::iter::once(word).chain(word.split('-').filter(move |part| !part.is_empty() && *part != word)))
+}
+
+/// Search terms from the subgroup of an emoji, such as `mammal` for `animal-mammal`. Must
+/// match `index::subgroup_terms` in the crate.
+fn subgroup_terms(subgroup: &str) -> impl Iterator<Item = &str> {
+    subgroup.split(['-', ' ', '&']).filter(|t| !t.is_empty() && *t != "other")
+}
+
+/// Generate the `kaomoji_data.rs` included by `src/emoji/kaomoji_data.rs` from the vendored
+/// `data/kaomoji.tsv`.
+///
+/// Each line is `text<TAB>name<TAB>keywords`, keywords separated by commas; lines starting with
+/// `#` are comments. Names and keywords are English, which every locale chain falls back to.
+fn generate_kaomoji(tsv: &str) -> String {
+    let mut out = String::from("// This file is @generated by build.rs\n\n");
+    out.push_str("use crate::emoji::emoji_data::{Emoji, Localized, Status};\n\n");
+    out.push_str("/// Kaomoji and text emoticons from `data/kaomoji.tsv`.\npub static KAOMOJI: &[Emoji] = &[\n");
+    for line in tsv.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#')) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [text, name, keywords] = fields[..] else {
+            panic!("Expected text, name and keywords in kaomoji.tsv: {:?}", line);
+        };
+        let keywords: Vec<String> = keywords
+            .split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(|k| format!("{:?}", k))
+            .collect();
+        out.push_str(&format!(
+            "    Emoji {{ ch: {:?}, localized: &[Localized {{ locale: \"en\", name: {:?}, keywords: &[{}] }}], category: \"Kaomoji\", subgroup: \"\", version: (0, 0), status: Status::FullyQualified, skin_tone_variants: None }},\n",
+            text, name.trim(), keywords.join(", ")
+        ));
+    }
+    out.push_str("];\n");
+    out
+}
+
+/// Parse `data/Blocks.txt` into (first, last, name) ranges.
+fn parse_blocks(text: &str) -> Vec<(u32, u32, String)> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .filter_map(|line| {
+            let (range, name) = line.split_once(';')?;
+            let (first, last) = range.split_once("..")?;
+            let first = u32::from_str_radix(first.trim(), 16).ok()?;
+            let last = u32::from_str_radix(last.trim(), 16).ok()?;
+            Some((first, last, name.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Generate the `unicode_data.rs` included by `src/emoji/unicode_data.rs` from the vendored
+/// `data/UnicodeData.txt` and `data/Blocks.txt`.
+///
+/// Every character with a name of its own is listed, with its block as category. Controls,
+/// surrogates, private use and the ranges named by codepoint (CJK ideographs, Hangul syllables,
+/// Tangut) are left out.
+fn generate_unicode(unicode_data: &str, blocks: &str) -> String {
+    let blocks = parse_blocks(blocks);
+    let mut out = String::from("// This file is @generated by build.rs\n\n");
+    out.push_str("use crate::emoji::emoji_data::{Emoji, Localized, Status};\n\n");
+    out.push_str("/// Unicode blocks from `data/Blocks.txt`: first and last codepoint, and name.\npub static BLOCKS: &[(u32, u32, &str)] = &[\n");
+    for (first, last, name) in &blocks {
+        out.push_str(&format!("    (0x{:04X}, 0x{:04X}, {:?}),\n", first, last, name));
+    }
+    out.push_str("];\n\n");
+    out.push_str("macro_rules! c {\n    ($ch:literal, $name:literal, $block:literal) => {\n        Emoji { ch: $ch, localized: &[Localized { locale: \"en\", name: $name, keywords: &[] }], category: $block, subgroup: \"\", version: (0, 0), status: Status::FullyQualified, skin_tone_variants: None }\n    };\n}\n\n");
+    out.push_str("/// Named characters from `data/UnicodeData.txt`, by codepoint, with their block as category.\npub static CHARACTERS: &[Emoji] = &[\n");
+    for line in unicode_data.lines() {
+        let fields: Vec<&str> = line.split(';').collect();
+        if fields.len() < 3 || fields[1].starts_with('<') || matches!(fields[2], "Cc" | "Cs" | "Co") {
+            continue;
+        }
+        let codepoint = u32::from_str_radix(fields[0], 16).expect("Invalid codepoint in UnicodeData.txt");
+        let block = blocks
+            .iter()
+            .find(|(first, last, _)| (*first..=*last).contains(&codepoint))
+            .map_or("No Block", |(_, _, name)| name.as_str());
+        out.push_str(&format!(
+            "    c!(\"\\u{{{:X}}}\", {:?}, {:?}),\n",
+            codepoint,
+            fields[1].to_lowercase(),
+            block
+        ));
+    }
+    out.push_str("];\n");
+    out
+}
+
 fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let out_dir = Path::new(&out_dir);
+
+    // Stylesheets and icons, registered at startup with `resources::register`
+    glib_build_tools::compile_resources(&["data", "packaging"], "data/emoji-picker.gresource.xml", "emoji-picker.gresource");
+
+    // Kaomoji, vendored in the repository
+    println!("cargo:rerun-if-changed=data/kaomoji.tsv");
+    let kaomoji = fs::read_to_string("data/kaomoji.tsv").expect("Failed to read data/kaomoji.tsv");
+    fs::write(out_dir.join("kaomoji_data.rs"), generate_kaomoji(&kaomoji))
+        .expect("Failed to write kaomoji_data.rs");
+
+    // Unicode character database, vendored in the repository
+    println!("cargo:rerun-if-changed=data/UnicodeData.txt");
+    println!("cargo:rerun-if-changed=data/Blocks.txt");
+    let unicode_data = fs::read_to_string("data/UnicodeData.txt").expect("Failed to read data/UnicodeData.txt");
+    let blocks = fs::read_to_string("data/Blocks.txt").expect("Failed to read data/Blocks.txt");
+    fs::write(out_dir.join("unicode_data.rs"), generate_unicode(&unicode_data, &blocks))
+        .expect("Failed to write unicode_data.rs");
 
     // Read emoji-test.txt from the new location
+    println!("cargo:rerun-if-changed=data/downloaded/emoji-test.txt");
     let emoji_test = fs::read_to_string("data/downloaded/emoji-test.txt").expect("Download emoji-test.txt first!");
 
 
-    // Parse CLDR for English and Dutch from both annotation sources
-    let cldr_en_main = parse_cldr_keywords("data/downloaded/common/annotations/en.xml");
-    let cldr_en_derived = parse_cldr_keywords("data/downloaded/common/annotationsDerived/en.xml");
-    let cldr_nl_main = parse_cldr_keywords("data/downloaded/common/annotations/nl.xml");
-    let cldr_nl_derived = parse_cldr_keywords("data/downloaded/common/annotationsDerived/nl.xml");
+    // Parse CLDR for the configured locales from both annotation sources
+    let locales: Vec<LocaleAnnotations> = configured_locales().iter().map(|l| load_locale(l)).collect();
+    for dir in ANNOTATION_DIRS.iter().filter(|dir| Path::new(dir).is_dir()) {
+        println!("cargo:rerun-if-changed={}", dir);
+    }
 
     // Helper to merge tts and keywords from both sources
     fn merge_cldr(
@@ -132,20 +356,38 @@ fn main() {
     ];
     use std::collections::HashMap;
     let mut base_to_skin_tones: HashMap<String, [Option<String>; 5]> = HashMap::new();
-    let mut emoji_rows = Vec::new();
+    // Emoji, names and keywords per locale, (category, subgroup, version, status) and the
+    // emoji without its skin tone
+    type EmojiRow = (String, Vec<(String, Vec<String>)>, (String, String, (u8, u8), &'static str), String);
+    let mut emoji_rows: Vec<EmojiRow> = Vec::new();
+    // Rows that are not fully-qualified: forms missing a presentation selector, and components
+    let mut other_rows: Vec<EmojiRow> = Vec::new();
     let mut current_category = "";
-    let re = Regex::new(r"^([0-9A-F ]+); fully-qualified").unwrap();
+    let mut current_subgroup = "";
+    // Code points, status, then a comment with the emoji, its version and its name, e.g.
+    // `1F600 ; fully-qualified # 😀 E1.0 grinning face`
+    let re = Regex::new(r"^([0-9A-F ]+?)\s*;\s*([a-z-]+)\s*#\s*\S+\s+E(\d+)\.(\d+)\s+(.+)$").unwrap();
 
     for line in emoji_test.lines() {
         if line.starts_with("# group:") {
             current_category = line.trim_start_matches("# group:").trim();
+        } else if line.starts_with("# subgroup:") {
+            current_subgroup = line.trim_start_matches("# subgroup:").trim();
         } else if let Some(caps) = re.captures(line) {
+            let status = match &caps[2] {
+                "fully-qualified" => "FullyQualified",
+                "minimally-qualified" => "MinimallyQualified",
+                "unqualified" => "Unqualified",
+                "component" => "Component",
+                other => panic!("Unknown status in emoji-test.txt: {:?}", other),
+            };
+            let version: (u8, u8) = (caps[3].parse().unwrap(), caps[4].parse().unwrap());
             let codepoints = caps[1].split_whitespace()
                 .map(|cp| u32::from_str_radix(cp, 16).unwrap())
                 .collect::<Vec<_>>();
             let ch: String = codepoints.iter().filter_map(|&c| char::from_u32(c)).collect();
             let codepoint_key = caps[1].to_string();
-            let name_en = line.split('#').nth(1).unwrap().split_whitespace().skip(1).collect::<Vec<_>>().join(" ");
+            let name_en = caps[5].trim().to_string();
 
             // CLDR lookups (use codepoint string as key)
             // Try direct lookup, then fallback by removing FE0F (VS16) if present
@@ -166,8 +408,14 @@ fn main() {
                 }
             };
 
-            let (_name_en_cldr, mut keywords_en) = lookup_with_fallback(&cldr_en_main, &cldr_en_derived, &codepoint_key, &name_en);
-            let (name_nl, mut keywords_nl) = lookup_with_fallback(&cldr_nl_main, &cldr_nl_derived, &codepoint_key, &name_en);
+            // English names come from emoji-test.txt, other locales fall back to them
+            let mut localized: Vec<(String, Vec<String>)> = locales
+                .iter()
+                .map(|l| {
+                    let (name, keywords) = lookup_with_fallback(&l.main, &l.derived, &codepoint_key, &name_en);
+                    if l.locale == "en" { (name_en.clone(), keywords) } else { (name, keywords) }
+                })
+                .collect();
 
             // Detect if this is a skin tone variant
             let mut is_skin_tone = false;
@@ -185,58 +433,99 @@ fn main() {
                 }
             }
             let base_ch: String = base.iter().filter_map(|&c| char::from_u32(c)).collect();
-            if is_skin_tone {
+            if is_skin_tone && status != "FullyQualified" {
+                // Toned forms missing a selector have no entry: the toned ones are variants
+                continue;
+            } else if is_skin_tone {
                 // Store in base_to_skin_tones
                 if let Some(idx) = skin_idx {
                     base_to_skin_tones.entry(base_ch.clone()).or_insert([None, None, None, None, None])[idx] = Some(ch.clone());
                 }
             } else {
-                // Fallback: if keywords_en is empty, try to get from lightest skin tone variant
-                if keywords_en.is_empty() {
-                    for (mod_cp, _) in skin_tone_mods.iter() {
-                        let mut skin_codepoints = codepoints.clone();
-                        skin_codepoints.push(u32::from_str_radix(mod_cp, 16).unwrap());
-                        let skin_key = skin_codepoints.iter().map(|c| format!("{:X}", c)).collect::<Vec<_>>().join(" ");
-                        let (_tts, kws) = merge_cldr(&cldr_en_main, &cldr_en_derived, &skin_key, "");
-                        if !kws.is_empty() {
-                            keywords_en = kws;
-                            break;
-                        }
+                // Fallback: if a locale has no keywords, try to get them from the lightest skin tone variant
+                for (l, (_, keywords)) in locales.iter().zip(localized.iter_mut()) {
+                    if !keywords.is_empty() {
+                        continue;
                     }
-                }
-                if keywords_nl.is_empty() {
                     for (mod_cp, _) in skin_tone_mods.iter() {
                         let mut skin_codepoints = codepoints.clone();
                         skin_codepoints.push(u32::from_str_radix(mod_cp, 16).unwrap());
                         let skin_key = skin_codepoints.iter().map(|c| format!("{:X}", c)).collect::<Vec<_>>().join(" ");
-                        let (_tts, kws) = merge_cldr(&cldr_nl_main, &cldr_nl_derived, &skin_key, "");
+                        let (_tts, kws) = merge_cldr(&l.main, &l.derived, &skin_key, "");
                         if !kws.is_empty() {
-                            keywords_nl = kws;
+                            *keywords = kws;
                             break;
                         }
                     }
                 }
-                emoji_rows.push((ch.clone(), name_en, keywords_en, name_nl, keywords_nl, current_category.to_string(), base_ch.clone()));
+                let metadata = (current_category.to_string(), current_subgroup.to_string(), version, status);
+                if status == "FullyQualified" {
+                    emoji_rows.push((ch.clone(), localized, metadata, base_ch.clone()));
+                } else {
+                    other_rows.push((ch.clone(), localized, metadata, base_ch.clone()));
+                }
             }
         }
     }
 
+    // The subgroup is searched too, as English keywords
+    let index_entries: Vec<Vec<(String, Vec<String>)>> = emoji_rows
+        .iter()
+        .map(|(_, localized, (_, subgroup, _, _), _)| {
+            let mut localized = localized.clone();
+            if let Some(i) = locales.iter().position(|l| l.locale == "en") {
+                for term in subgroup_terms(subgroup) {
+                    if !localized[i].1.iter().any(|k| k == term) {
+                        localized[i].1.push(term.to_string());
+                    }
+                }
+            }
+            localized
+        })
+        .collect();
+    fs::write(out_dir.join("search_index.rs"), generate_search_index(&index_entries))
+        .expect("Failed to write search_index.rs");
+
     // Generate Rust code with skin tone support
-    let mut out = String::from("// This file is @generated by build.rs\n\n#[derive(Clone, Copy, PartialEq, Eq, Debug)]\npub enum SkinTone {\n    Default,\n    Light,\n    MediumLight,\n    Medium,\n    MediumDark,\n    Dark,\n}\n\n#[derive(Clone)]\npub struct Emoji {\n    pub ch: &'static str,\n    pub name_en: &'static str,\n    pub keywords_en: &'static [&'static str],\n    pub name_nl: &'static str,\n    pub keywords_nl: &'static [&'static str],\n    pub category: &'static str,\n    pub skin_tone_variants: Option<[&'static str; 5]>,\n}\n\npub static mut PREFERRED_SKIN_TONE: SkinTone = SkinTone::Default;\n\npub static EMOJIS: &[Emoji] = &[\n");
-    for (ch, name_en, keywords_en, name_nl, keywords_nl, category, base_ch) in emoji_rows {
-        let kw_en: Vec<String> = keywords_en.iter().map(|k| format!("\"{}\"", k)).collect();
-        let kw_nl: Vec<String> = keywords_nl.iter().map(|k| format!("\"{}\"", k)).collect();
-        let skin_opt = base_to_skin_tones.get(&ch).or_else(|| base_to_skin_tones.get(&base_ch));
+    let mut out = String::from("// This file is @generated by build.rs\n\n");
+    let locale_names: Vec<String> = locales.iter().map(|l| format!("{:?}", l.locale)).collect();
+    out.push_str(&format!(
+        "/// CLDR locales compiled into `EMOJIS` (set with `EMOJI_PICKER_LOCALES` at build time).\npub static LOCALES: &[&str] = &[{}];\n\n",
+        locale_names.join(", ")
+    ));
+    out.push_str("/// Name and keywords of an emoji in one locale.\n#[derive(Clone, Debug)]\npub struct Localized {\n    pub locale: &'static str,\n    pub name: &'static str,\n    pub keywords: &'static [&'static str],\n}\n\n");
+    out.push_str("/// How completely an emoji carries its presentation selectors, as in `emoji-test.txt`.\n#[derive(Clone, Copy, Debug, PartialEq, Eq)]\npub enum Status {\n    Component,\n    FullyQualified,\n    MinimallyQualified,\n    Unqualified,\n}\n\n");
+    out.push_str("#[derive(Clone)]\npub struct Emoji {\n    pub ch: &'static str,\n    /// Names and keywords per locale, in `LOCALES` order.\n    pub localized: &'static [Localized],\n    pub category: &'static str,\n    /// Subgroup within the category, such as `face-smiling`; empty for text that is not an emoji.\n    pub subgroup: &'static str,\n    /// Emoji version that introduced it, such as `(13, 0)` for E13.0; `(0, 0)` for text that is\n    /// not an emoji.\n    pub version: (u8, u8),\n    /// Fully-qualified for `EMOJIS`, and for text that is not an emoji; see `OTHER_FORMS`.\n    pub status: Status,\n    /// Skin tone variants from light to dark (see `SkinTone::TONED`).\n    pub skin_tone_variants: Option<[&'static str; 5]>,\n}\n\n");
+    let entry = |(ch, localized, (category, subgroup, version, status), base_ch): &EmojiRow, toned: bool| {
+        let localized: Vec<String> = locales
+            .iter()
+            .zip(localized.iter())
+            .map(|(l, (name, keywords))| {
+                let keywords: Vec<String> = keywords.iter().map(|k| format!("{:?}", k)).collect();
+                format!("Localized {{ locale: {:?}, name: {:?}, keywords: &[{}] }}", l.locale, name, keywords.join(", "))
+            })
+            .collect();
+        let skin_opt = base_to_skin_tones.get(ch).or_else(|| base_to_skin_tones.get(base_ch)).filter(|_| toned);
         let skin_str = if let Some(arr) = skin_opt {
             let arr_str: Vec<String> = arr.iter().map(|opt| opt.as_ref().map(|s| format!("\"{}\"", s)).unwrap_or("None".to_string())).collect();
             format!("Some([{}])", arr_str.join(", "))
         } else {
             "None".to_string()
         };
-        out.push_str(&format!(
-            "    Emoji {{ ch: \"{}\", name_en: \"{}\", keywords_en: &[{}], name_nl: \"{}\", keywords_nl: &[{}], category: \"{}\", skin_tone_variants: {} }},\n",
-            ch, name_en, kw_en.join(", "), name_nl, kw_nl.join(", "), category, skin_str
-        ));
+        format!(
+            "    Emoji {{ ch: \"{}\", localized: &[{}], category: \"{}\", subgroup: {:?}, version: ({}, {}), status: Status::{}, skin_tone_variants: {} }},\n",
+            ch, localized.join(", "), category, subgroup, version.0, version.1, status, skin_str
+        )
+    };
+    out.push_str("/// The fully-qualified emoji, which are browsed and searched.\npub static EMOJIS: &[Emoji] = &[\n");
+    for row in &emoji_rows {
+        out.push_str(&entry(row, true));
+    }
+    out.push_str("];\n\n");
+    // The skin tones belong to the fully-qualified form
+    out.push_str("/// The other rows of emoji-test.txt: components, and emoji missing presentation selectors\n/// (minimally-qualified or unqualified). Only lookups know them.\npub static OTHER_FORMS: &[Emoji] = &[\n");
+    for row in &other_rows {
+        out.push_str(&entry(row, false));
     }
     out.push_str("];\n");
 
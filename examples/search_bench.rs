@@ -0,0 +1,52 @@
+//! Per-keystroke search latency: full table scan vs. the precomputed search index.
+//!
+//! Simulates typing each query one character at a time and times every intermediate search.
+//! Run with `cargo run --release --example search_bench`.
+
+use emoji_picker::emoji::emoji_data::{EMOJIS, LOCALES};
+use emoji_picker::emoji::search::{rank, rank_indexed};
+use emoji_picker::emoji::usage::UsageHistory;
+use std::time::{Duration, Instant};
+
+const QUERIES: &[&str] = &[
+    "grinning face",
+    "heart",
+    "thumbs up",
+    "party popper",
+    "unicron",
+    "thmbup",
+    "koffie",
+    "firefigther",
+];
+
+const ROUNDS: usize = 5;
+
+fn keystrokes() -> Vec<String> {
+    QUERIES
+        .iter()
+        .flat_map(|q| q.char_indices().map(move |(i, c)| q[..i + c.len_utf8()].to_string()))
+        .collect()
+}
+
+fn measure(name: &str, search: impl Fn(&str) -> usize) {
+    let mut timings: Vec<Duration> = Vec::new();
+    for _ in 0..ROUNDS {
+        for prefix in keystrokes() {
+            let start = Instant::now();
+            std::hint::black_box(search(&prefix));
+            timings.push(start.elapsed());
+        }
+    }
+    timings.sort();
+    let mean = timings.iter().sum::<Duration>() / timings.len() as u32;
+    let p95 = timings[timings.len() * 95 / 100];
+    let max = timings[timings.len() - 1];
+    println!("{:<8} mean {:>9.1?}  p95 {:>9.1?}  max {:>9.1?}", name, mean, p95, max);
+}
+
+fn main() {
+    let usage = UsageHistory::default();
+    println!("{} emoji, {} keystrokes x {} rounds", EMOJIS.len(), keystrokes().len(), ROUNDS);
+    measure("scan", |q| rank(EMOJIS, q, LOCALES, &usage, 0).len());
+    measure("indexed", |q| rank_indexed(q, LOCALES, &usage, 0).len());
+}
@@ -9,6 +9,8 @@ Source0:        %{name}-%{version}.tar.gz
 
 BuildRequires:  rust, cargo, pkgconfig, gtk4-devel, libadwaita-devel, glib2-devel
 Requires:       gtk4, libadwaita, glib2
+# output_mode = "insert" types the emoji with these programs; without them it copies
+Recommends:     wtype, xdotool
 
 # Binary builds are architecture specific
 BuildArch:      %{_arch}
@@ -36,6 +38,10 @@ install -m 0644 ../../.build/flatpak/emoji-picker.desktop %{buildroot}%{_datadir
 install -d %{buildroot}%{_datadir}/metainfo
 install -m 0644 ../../.build/flatpak/nl.dibitat.emoji_picker.metainfo.xml %{buildroot}%{_datadir}/metainfo/nl.dibitat.emoji_picker.metainfo.xml
 
+# D-Bus activation starts a background instance (see `emoji-picker --daemon`)
+install -d %{buildroot}%{_datadir}/dbus-1/services
+install -m 0644 packaging/nl.dibitat.emoji_picker.service %{buildroot}%{_datadir}/dbus-1/services/nl.dibitat.emoji_picker.service
+
 # install main svg icon
 install -d %{buildroot}%{_datadir}/icons/hicolor/scalable/apps
 install -m 0644 packaging/emoji-picker.svg %{buildroot}%{_datadir}/icons/hicolor/scalable/apps/nl.dibitat.emoji_picker.svg
@@ -47,6 +53,7 @@ install -m 0644 packaging/emoji-picker.svg %{buildroot}%{_datadir}/icons/hicolor
 %{_datadir}/applications/emoji-picker.desktop
 %{_datadir}/metainfo/*
 %{_datadir}/icons/*
+%{_datadir}/dbus-1/services/nl.dibitat.emoji_picker.service
 
 %changelog
 * Thu Jan 01 2026 Packager <packager@example.com> - 0.1.0-1
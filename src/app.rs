@@ -1,29 +1,91 @@
 use gtk4::prelude::*;
-// ...existing code...
-use gtk4::Application;
+use gtk4::gio::ApplicationFlags;
+use gtk4::glib;
+use libadwaita as adw;
 
 // (removed redundant mod ui;)
+use crate::dbus;
+use crate::resources;
+use crate::trace;
 use crate::ui::main_window::MainWindow;
 
+/// Command line option that keeps the picker running in the background.
+const DAEMON_OPTION: &str = "daemon";
+
+/// Run the picker.
+///
+/// With `--daemon`, or when started by D-Bus activation with `--gapplication-service`, the
+/// application holds itself alive: the window is built up front without being shown, closing
+/// it only hides it, and each later `emoji-picker` invocation presents it again through
+/// GApplication single-instance activation.
 pub fn run() {
+    // Start the clock of the startup trace (see EMOJI_PICKER_TRACE)
+    trace::start();
+
     // Initialize logging
     env_logger::init();
 
-    // Record start time
-    let _start_time = std::time::Instant::now();
-
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
+    if let Err(err) = resources::register() {
+        log::error!("Could not register the bundled resources: {}", err);
+    }
+
     let window_ref: Rc<RefCell<Option<MainWindow>>> = Rc::new(RefCell::new(None));
-    let app = Application::builder()
+    let app = adw::Application::builder()
         .application_id("nl.dibitat.emoji_picker")
         .build();
+    app.add_main_option(
+        DAEMON_OPTION,
+        glib::Char::from(b'd'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Keep running in the background and show the picker when started again",
+        None,
+    );
+
+    // Set for `--daemon`: the first activation only starts the background instance
+    let daemon = Rc::new(Cell::new(false));
+    let daemon_clone = daemon.clone();
+    app.connect_handle_local_options(move |app, options| {
+        if !options.contains(DAEMON_OPTION) {
+            return -1;
+        }
+        // Register early, so a second daemon exits instead of showing the first one's window
+        if let Err(err) = app.register(None::<&gtk4::gio::Cancellable>) {
+            log::error!("Could not register the application: {}", err);
+            return 1;
+        }
+        if app.is_remote() {
+            log::info!("emoji-picker is already running in the background");
+            return 0;
+        }
+        daemon_clone.set(true);
+        -1
+    });
+
+    // Keeps a background instance alive; released only when the process ends
+    let hold_guard = Rc::new(RefCell::new(None));
+    let window_ref_clone = window_ref.clone();
+    let daemon_clone = daemon.clone();
+    app.connect_startup(move |app| {
+        // Bundled under the resource base path (see data/emoji-picker.gresource.xml)
+        gtk4::Window::set_default_icon_name("nl.dibitat.emoji_picker");
+        if daemon_clone.get() || app.flags().contains(ApplicationFlags::IS_SERVICE) {
+            log::info!("Running in the background");
+            *hold_guard.borrow_mut() = Some(app.hold());
+            *window_ref_clone.borrow_mut() = Some(build_window(app, true));
+        }
+    });
 
     let window_ref_clone = window_ref.clone();
     app.connect_activate(move |app| {
+        if daemon.replace(false) {
+            return;
+        }
         let mut win = window_ref_clone.borrow_mut();
         if win.is_none() {
-            *win = Some(MainWindow::new(app));
+            *win = Some(build_window(app, false));
         }
         if let Some(ref window) = *win {
             window.present();
@@ -31,4 +93,18 @@ pub fn run() {
     });
 
     app.run();
-}
\ No newline at end of file
+}
+
+/// Build the picker window and export the D-Bus interface backed by it.
+fn build_window(app: &adw::Application, background: bool) -> MainWindow {
+    let window = MainWindow::new(app, background);
+    trace::mark("window built");
+    if let Some(connection) = app.dbus_connection() {
+        let shown = window.clone();
+        match dbus::export(&connection, window.controller(), move |query| shown.show_search(query)) {
+            Ok(_) => log::info!("Exported {} at {}", dbus::INTERFACE_NAME, dbus::OBJECT_PATH),
+            Err(err) => log::warn!("Could not export the D-Bus interface: {}", err),
+        }
+    }
+    window
+}
@@ -0,0 +1,488 @@
+//! Headless command-line subcommands: search and look up emoji without opening a window.
+//!
+//! `emoji-picker search tada` and friends print straight from `EMOJIS` and the user's
+//! snippets (see `collections`), ranked like the search field (including the usage history),
+//! and never touch GTK, so they also work over SSH and in CI.
+//! Only `--pick-from-stdin` needs a display (see `launcher`).
+//! Any other arguments start the picker itself.
+
+use crate::clipboard;
+use crate::emoji::collections;
+use crate::emoji::emoji_data::{Emoji, Status};
+use crate::emoji::usage::{self, UsageHistory};
+use crate::launcher;
+use crate::output::{self, Inserter};
+use crate::settings::{self, OutputMode, Settings};
+use crate::ui::app_controller::EmojiPickerController;
+use std::collections::hash_map::RandomState;
+use std::fmt::Write as _;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, Write};
+
+pub const USAGE: &str = "\
+Usage:
+  emoji-picker [--daemon]                  Show the picker
+  emoji-picker search [OPTIONS] QUERY...   Print emoji matching QUERY, best first
+  emoji-picker info [OPTIONS] EMOJI        Print details of one emoji
+  emoji-picker list [OPTIONS]              Print all emoji
+  emoji-picker random [OPTIONS]            Print one random emoji
+  emoji-picker --dmenu [--template T]      Print one line per emoji for dmenu, rofi or fuzzel
+  emoji-picker --pick-from-stdin           Copy or insert the emoji in the line read from stdin
+
+Options:
+  -c, --category NAME   Only emoji in category NAME (list, random)
+  -n, --limit N         Print at most N results (search)
+  -f, --format FORMAT   Output as human (default), tsv or json
+      --tsv, --json     Short for --format tsv and --format json
+  -t, --template T      Line template (--dmenu), default \"{emoji} {name} — {keywords}\";
+                        also {category} and {codepoints}";
+
+/// How results are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned text for reading in a terminal.
+    #[default]
+    Human,
+    /// One emoji per line: emoji, name, category and comma-separated keywords.
+    Tsv,
+    /// A JSON array of objects (a single object for `info`).
+    Json,
+}
+
+/// A subcommand and its arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Search { query: String, limit: Option<usize> },
+    Info { emoji: String },
+    List { category: Option<String> },
+    Random { category: Option<String> },
+    /// Launcher lines, see `launcher`.
+    Dmenu { template: Option<String> },
+    PickFromStdin,
+    /// Keep `text` on the clipboard until something else is copied, in the background process
+    /// started by `clipboard::copy_in_background`. Not listed in [`USAGE`].
+    ServeClipboard { text: String },
+    Help,
+}
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub command: Command,
+    pub format: Format,
+}
+
+/// Parse the arguments after the program name.
+///
+/// Returns `Ok(None)` when the first argument is not a subcommand, so the picker should start.
+pub fn parse(args: &[String]) -> Result<Option<Invocation>, String> {
+    let Some((name, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+    if !matches!(
+        name.as_str(),
+        "search" | "info" | "list" | "random" | "help" | "--dmenu" | "--pick-from-stdin" | clipboard::SERVE_COMMAND
+    ) {
+        return Ok(None);
+    }
+    let mut format = Format::default();
+    let mut category = None;
+    let mut limit = None;
+    let mut template = None;
+    let mut words = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = |option: &str| rest.next().cloned().ok_or_else(|| format!("{} needs a value", option));
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match value(arg)?.as_str() {
+                    "human" => Format::Human,
+                    "tsv" => Format::Tsv,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            "--tsv" => format = Format::Tsv,
+            "--json" => format = Format::Json,
+            "-c" | "--category" => category = Some(value(arg)?),
+            "-t" | "--template" => template = Some(value(arg)?),
+            "-n" | "--limit" => {
+                let n = value(arg)?;
+                limit = Some(n.parse().map_err(|_| format!("invalid limit '{}'", n))?);
+            }
+            "-h" | "--help" => return Ok(Some(Invocation { command: Command::Help, format })),
+            "--" => words.extend(rest.by_ref().cloned()),
+            option if option.starts_with('-') && option.len() > 1 => {
+                return Err(format!("unknown option '{}'", option));
+            }
+            word => words.push(word.to_string()),
+        }
+    }
+    let takes_words = matches!(name.as_str(), "search" | "info" | clipboard::SERVE_COMMAND);
+    if let (false, Some(word)) = (takes_words, words.first()) {
+        return Err(format!("unexpected argument '{}'", word));
+    }
+    let command = match name.as_str() {
+        "search" if words.is_empty() => return Err("search needs a query".to_string()),
+        "search" => Command::Search { query: words.join(" "), limit },
+        "info" => match <[String; 1]>::try_from(words) {
+            Ok([emoji]) => Command::Info { emoji },
+            Err(_) => return Err("info needs exactly one emoji".to_string()),
+        },
+        "list" => Command::List { category },
+        "random" => Command::Random { category },
+        "--dmenu" => Command::Dmenu { template },
+        "--pick-from-stdin" => Command::PickFromStdin,
+        clipboard::SERVE_COMMAND => match <[String; 1]>::try_from(words) {
+            Ok([text]) => Command::ServeClipboard { text },
+            Err(_) => return Err(format!("{} needs exactly one text", clipboard::SERVE_COMMAND)),
+        },
+        _ => Command::Help,
+    };
+    Ok(Some(Invocation { command, format }))
+}
+
+/// Run `invocation` with the usage history and settings of the user, printing to stdout.
+///
+/// Returns the process exit code.
+pub fn run(invocation: &Invocation) -> i32 {
+    if let Command::ServeClipboard { text } = &invocation.command {
+        return match clipboard::copy_and_wait(text) {
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("emoji-picker: {}", err);
+                2
+            }
+        };
+    }
+    let usage_path = usage::default_path();
+    let history = UsageHistory::load(&usage_path, usage::DEFAULT_CAPACITY);
+    // Only an actual pick counts as usage; looking emoji up does not
+    let usage_path = (invocation.command == Command::PickFromStdin).then_some(usage_path);
+    let settings = Settings::load(&settings::default_path());
+    let mut controller = EmojiPickerController::with_usage(collections::all_emojis(), history, usage_path).with_settings(settings, None);
+    let result = match invocation.command {
+        Command::PickFromStdin => pick_line(&mut controller, &mut io::stdin().lock())
+            .and_then(|picked| deliver(&picked, controller.settings.output_mode)),
+        _ => execute(invocation, &controller, &mut io::stdout().lock()),
+    };
+    match result {
+        Ok(()) => 0,
+        // Quietly stop when piped into e.g. `head`
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => 0,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            eprintln!("emoji-picker: {}", err);
+            1
+        }
+        Err(err) => {
+            eprintln!("emoji-picker: {}", err);
+            2
+        }
+    }
+}
+
+/// Run `invocation` against `controller`, writing the results to `out`.
+///
+/// Unknown emoji and categories are reported as `NotFound` errors. `--pick-from-stdin` does
+/// not write anything; [`run`] handles it with [`pick_line`].
+pub fn execute(invocation: &Invocation, controller: &EmojiPickerController, out: &mut impl Write) -> io::Result<()> {
+    let not_found = |message: String| io::Error::new(io::ErrorKind::NotFound, message);
+    let emojis: Vec<Emoji> = match &invocation.command {
+        Command::Help => return writeln!(out, "{}", USAGE),
+        Command::Search { query, limit } => {
+            let mut results = controller.rank(query);
+            results.truncate(limit.unwrap_or(usize::MAX));
+            results
+        }
+        Command::Info { emoji } => {
+            let emoji = controller.lookup(emoji).ok_or_else(|| not_found(format!("'{}' is not a known emoji", emoji)))?;
+            return write_info(out, emoji, &controller.locales, invocation.format);
+        }
+        Command::List { category } => in_category(controller, category.as_deref())?,
+        Command::Dmenu { template } => {
+            let template = template
+                .as_deref()
+                .or(controller.settings.line_template.as_deref())
+                .unwrap_or(launcher::DEFAULT_LINE_TEMPLATE);
+            for line in launcher::lines(controller, template, &controller.settings.skin_tone) {
+                writeln!(out, "{}", line)?;
+            }
+            return Ok(());
+        }
+        Command::PickFromStdin | Command::ServeClipboard { .. } => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "this command does not print, see `run`"));
+        }
+        Command::Random { category } => {
+            let candidates = in_category(controller, category.as_deref())?;
+            let seed = RandomState::new().build_hasher().finish();
+            candidates.get(seed as usize % candidates.len().max(1)).cloned().into_iter().collect()
+        }
+    };
+    write_list(out, &emojis, &controller.locales, invocation.format)
+}
+
+/// Read the line chosen in a launcher from `input` and record its emoji as picked.
+///
+/// An empty line (the launcher was cancelled) and a line without emoji are `NotFound` errors.
+pub fn pick_line(controller: &mut EmojiPickerController, input: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let picked = launcher::pick_from_line(controller, &line)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no emoji in '{}'", line.trim())))?
+        .to_string();
+    controller.record_pick(&picked);
+    Ok(picked)
+}
+
+/// Type `text` into the focused application or copy it, like the picker does.
+///
+/// Without a window to hide, typing can start right away; the launcher has already closed.
+fn deliver(text: &str, mode: OutputMode) -> io::Result<()> {
+    if mode == OutputMode::Insert {
+        let inserted = gtk4::glib::MainContext::default().block_on(output::insert_with(&Inserter::detect(), text));
+        match inserted {
+            Ok(_) => return Ok(()),
+            Err(err) => log::warn!("Could not insert {}, copying it instead: {}", text, err),
+        }
+    }
+    clipboard::copy_in_background(text)
+}
+
+/// The shown emoji in `category` (matched case-insensitively), or all of them.
+fn in_category(controller: &EmojiPickerController, category: Option<&str>) -> io::Result<Vec<Emoji>> {
+    let shown = controller.shown_emojis();
+    let Some(category) = category else {
+        return Ok(shown);
+    };
+    let emojis: Vec<Emoji> = shown.into_iter().filter(|e| e.category.eq_ignore_ascii_case(category)).collect();
+    if emojis.is_empty() {
+        let mut categories: Vec<&str> = controller.all_emojis.iter().map(|e| e.category).collect();
+        categories.sort_unstable();
+        categories.dedup();
+        let message = format!("unknown category '{}' (categories: {})", category, categories.join(", "));
+        return Err(io::Error::new(io::ErrorKind::NotFound, message));
+    }
+    Ok(emojis)
+}
+
+fn write_list(out: &mut impl Write, emojis: &[Emoji], locales: &[&str], format: Format) -> io::Result<()> {
+    match format {
+        Format::Human => {
+            for emoji in emojis {
+                writeln!(out, "{}\t{}", emoji.ch, emoji.name(locales))?;
+            }
+        }
+        Format::Tsv => {
+            for emoji in emojis {
+                writeln!(out, "{}", tsv_line(emoji, locales))?;
+            }
+        }
+        Format::Json => {
+            let objects: Vec<String> = emojis.iter().map(|e| json_object(e, locales)).collect();
+            writeln!(out, "[{}]", objects.join(","))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_info(out: &mut impl Write, emoji: &Emoji, locales: &[&str], format: Format) -> io::Result<()> {
+    match format {
+        Format::Human => {
+            writeln!(out, "{}  {}", emoji.ch, emoji.name(locales))?;
+            writeln!(out, "Category:    {}", emoji.category)?;
+            if !emoji.subgroup.is_empty() {
+                writeln!(out, "Subgroup:    {}", emoji.subgroup)?;
+                writeln!(out, "Version:     {}", emoji_version(emoji))?;
+                writeln!(out, "Status:      {}", status_name(emoji.status))?;
+            }
+            writeln!(out, "Keywords:    {}", emoji.keywords(locales).join(", "))?;
+            writeln!(out, "Code points: {}", code_points(emoji.ch))?;
+            if let Some(variants) = emoji.skin_tone_variants {
+                writeln!(out, "Skin tones:  {}", variants.join(" "))?;
+            }
+            Ok(())
+        }
+        Format::Tsv => writeln!(out, "{}", tsv_line(emoji, locales)),
+        Format::Json => writeln!(out, "{}", json_object(emoji, locales)),
+    }
+}
+
+/// The code points of `text`, like `U+1F44D U+1F3FD`.
+pub(crate) fn code_points(text: &str) -> String {
+    text.chars().map(|c| format!("U+{:04X}", c as u32)).collect::<Vec<_>>().join(" ")
+}
+
+/// The emoji version of `emoji`, like `E13.0`.
+fn emoji_version(emoji: &Emoji) -> String {
+    format!("E{}.{}", emoji.version.0, emoji.version.1)
+}
+
+/// `status` as it is spelled in `emoji-test.txt`.
+fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::Component => "component",
+        Status::FullyQualified => "fully-qualified",
+        Status::MinimallyQualified => "minimally-qualified",
+        Status::Unqualified => "unqualified",
+    }
+}
+
+fn tsv_line(emoji: &Emoji, locales: &[&str]) -> String {
+    // Names and keywords never contain tabs or newlines, so no quoting is needed
+    format!("{}\t{}\t{}\t{}", emoji.ch, emoji.name(locales), emoji.category, emoji.keywords(locales).join(","))
+}
+
+fn json_object(emoji: &Emoji, locales: &[&str]) -> String {
+    let keywords: Vec<String> = emoji.keywords(locales).iter().map(|k| json_string(k)).collect();
+    let variants: Vec<String> = emoji.skin_tone_variants.iter().flatten().map(|v| json_string(v)).collect();
+    format!(
+        "{{\"emoji\":{},\"name\":{},\"category\":{},\"subgroup\":{},\"version\":{},\"status\":{},\"keywords\":[{}],\"code_points\":{},\"skin_tone_variants\":[{}]}}",
+        json_string(emoji.ch),
+        json_string(emoji.name(locales)),
+        json_string(emoji.category),
+        json_string(emoji.subgroup),
+        json_string(&emoji_version(emoji)),
+        json_string(status_name(emoji.status)),
+        keywords.join(","),
+        json_string(&code_points(emoji.ch)),
+        variants.join(","),
+    )
+}
+
+/// `text` as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::emoji_data::EMOJIS;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn controller() -> EmojiPickerController {
+        let mut controller = EmojiPickerController::new(EMOJIS.to_vec());
+        controller.locales = vec!["en"];
+        controller
+    }
+
+    fn output(line: &str) -> String {
+        let invocation = parse(&args(line)).unwrap().unwrap();
+        let mut out = Vec::new();
+        execute(&invocation, &controller(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_subcommands_and_options() {
+        assert_eq!(parse(&args("")), Ok(None));
+        assert_eq!(parse(&args("--daemon")), Ok(None));
+        assert_eq!(
+            parse(&args("search --json -n 3 party popper")),
+            Ok(Some(Invocation {
+                command: Command::Search { query: "party popper".to_string(), limit: Some(3) },
+                format: Format::Json,
+            }))
+        );
+        assert_eq!(
+            parse(&args("list --category Flags -f tsv")).unwrap().unwrap().command,
+            Command::List { category: Some("Flags".to_string()) }
+        );
+        assert!(parse(&args("search")).is_err());
+        assert!(parse(&args("info 👍 👋")).is_err());
+        assert!(parse(&args("list --format xml")).is_err());
+        assert!(parse(&args("random --bogus")).is_err());
+        assert_eq!(
+            parse(&args("--dmenu -t {emoji}")).unwrap().unwrap().command,
+            Command::Dmenu { template: Some("{emoji}".to_string()) }
+        );
+        assert!(parse(&args("--pick-from-stdin extra")).is_err());
+        assert_eq!(
+            parse(&args("--serve-clipboard -- -x")).unwrap().unwrap().command,
+            Command::ServeClipboard { text: "-x".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_dmenu_lines_can_be_picked_back() {
+        let out = output("--dmenu");
+        assert_eq!(out.lines().count(), EMOJIS.len());
+        let line = out.lines().find(|l| l.starts_with("🎉 ")).unwrap();
+        let mut controller = controller();
+        let picked = pick_line(&mut controller, &mut format!("{}\n", line).as_bytes()).unwrap();
+        assert_eq!(picked, "🎉");
+        assert_eq!(controller.recent_emojis()[0].ch, "🎉");
+        let err = pick_line(&mut controller, &mut "".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_search_uses_the_controller_ranking() {
+        let out = output("search --tsv -n 5 tada");
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 5);
+        let expected: Vec<_> = controller().rank("tada").iter().take(5).map(|e| e.ch).collect();
+        let printed: Vec<_> = lines.iter().map(|l| l.split('\t').next().unwrap()).collect();
+        assert_eq!(printed, expected);
+        let fields: Vec<_> = lines[0].split('\t').collect();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[1], "party popper");
+        assert_eq!(fields[2], "Activities");
+    }
+
+    #[test]
+    fn test_info_accepts_skin_tone_variants() {
+        let out = output("info 👍🏽");
+        assert!(out.lines().next().unwrap().starts_with("👍  ") && out.contains("thumbs up\n"));
+        assert!(out.contains("Code points: U+1F44D\n"));
+        assert!(out.contains("Subgroup:    hand-fingers-closed\nVersion:     E0.6\nStatus:      fully-qualified\n"));
+        assert!(out.contains("👍🏽"));
+        let json = output("info --json 🎉");
+        assert!(json.starts_with("{\"emoji\":\"🎉\",\"name\":\""));
+        assert!(json.contains("\"name\":\"party popper\",\"category\":\"Activities\",\"subgroup\":\"event\""));
+        assert!(json.contains("\"version\":\"E0.6\",\"status\":\"fully-qualified\""));
+        assert!(json.contains("\"code_points\":\"U+1F389\""));
+        let unqualified = output("info ☺");
+        assert!(unqualified.contains("smiling face\n") && unqualified.contains("Status:      unqualified\n"));
+    }
+
+    #[test]
+    fn test_list_and_random_respect_the_category() {
+        let flags = EMOJIS.iter().filter(|e| e.category == "Flags").count();
+        assert_eq!(output("list -c flags").lines().count(), flags);
+        let random = output("random --category Flags --tsv");
+        assert_eq!(random.lines().count(), 1);
+        assert!(random.contains("\tFlags\t"));
+        let mut out = Vec::new();
+        let invocation = parse(&args("list -c nonsense")).unwrap().unwrap();
+        let err = execute(&invocation, &controller(), &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("(categories: Activities, Animals & Nature, Flags, Food & Drink,"));
+    }
+
+    #[test]
+    fn test_json_output_is_escaped() {
+        assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\n\"");
+        let out = output("list --json");
+        assert!(out.starts_with("[{") && out.trim_end().ends_with("}]"));
+        assert_eq!(out.matches("\"emoji\":").count(), EMOJIS.len());
+    }
+}
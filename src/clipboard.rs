@@ -1,8 +1,55 @@
 use gtk4::gdk::Display;
 use gtk4::gdk::prelude::*;
+use gtk4::glib;
+use std::env;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+/// The hidden subcommand of the background process started by [`copy_in_background`].
+pub const SERVE_COMMAND: &str = "--serve-clipboard";
 
 pub fn copy(text: &str) {
     let display = Display::default().unwrap();
     let clipboard = display.clipboard();
     clipboard.set_text(text);
-}
\ No newline at end of file
+}
+
+/// Copy `text` from a process without windows, such as `--pick-from-stdin`, and return.
+///
+/// Like `wl-copy`, this leaves the clipboard to a copy of the program in the background that
+/// runs [`copy_and_wait`], so a launcher pipeline ends right away, with or without a clipboard
+/// manager.
+pub fn copy_in_background(text: &str) -> io::Result<()> {
+    Command::new(env::current_exe()?)
+        .args([SERVE_COMMAND, "--", text])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Out of the process group of the pipeline, so it does not get the signals meant for it
+        .process_group(0)
+        .spawn()?;
+    Ok(())
+}
+
+/// Copy `text` and keep it on the clipboard.
+///
+/// The clipboard only refers to this process, so it keeps running until another application
+/// (or a clipboard manager) takes the clipboard over.
+pub fn copy_and_wait(text: &str) -> Result<(), glib::BoolError> {
+    gtk4::init()?;
+    let display = Display::default().ok_or_else(|| glib::bool_error!("No default display found"))?;
+    let clipboard = display.clipboard();
+    clipboard.set_text(text);
+    let main_loop = glib::MainLoop::new(None, false);
+    clipboard.connect_changed({
+        let main_loop = main_loop.clone();
+        move |clipboard| {
+            if !clipboard.is_local() {
+                main_loop.quit();
+            }
+        }
+    });
+    main_loop.run();
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! D-Bus interface for scripting the picker from other tools.
+//!
+//! The primary instance exports [`INTERFACE_NAME`] at [`OBJECT_PATH`], next to the
+//! `org.gtk.Application` interfaces GApplication already exports under the
+//! `nl.dibitat.emoji_picker` bus name:
+//!
+//! - `Show(s query)`: present the picker, searching for `query` (empty to browse).
+//! - `Search(s query) -> a(ss)`: the matching emoji and their names, best first, ranked like
+//!   the search field.
+//! - `Lookup(s emoji) -> a{sv}`: metadata of one emoji (`emoji`, `name`, `category`,
+//!   `keywords`, `skin-tone-variants`), also for skin tone variants.
+//! - `Picked(s emoji)` signal: emitted for every emoji the user picks.
+//!
+//! For example:
+//!
+//! ```sh
+//! gdbus call --session --dest nl.dibitat.emoji_picker --object-path /nl/dibitat/emoji_picker \
+//!     --method nl.dibitat.emoji_picker.Picker.Search tada
+//! ```
+
+use crate::emoji::emoji_data::Emoji;
+use crate::ui::app_controller::EmojiPickerController;
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Object path of the picker, the same one GApplication uses for the application id.
+pub const OBJECT_PATH: &str = "/nl/dibitat/emoji_picker";
+
+/// Name of the exported interface.
+pub const INTERFACE_NAME: &str = "nl.dibitat.emoji_picker.Picker";
+
+/// Error returned by `Lookup` for text that is not a known emoji.
+pub const NOT_FOUND_ERROR: &str = "nl.dibitat.emoji_picker.Error.NotFound";
+
+const INVALID_ARGS_ERROR: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+const UNKNOWN_METHOD_ERROR: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="nl.dibitat.emoji_picker.Picker">
+    <method name="Show">
+      <arg type="s" name="query" direction="in"/>
+    </method>
+    <method name="Search">
+      <arg type="s" name="query" direction="in"/>
+      <arg type="a(ss)" name="results" direction="out"/>
+    </method>
+    <method name="Lookup">
+      <arg type="s" name="emoji" direction="in"/>
+      <arg type="a{sv}" name="metadata" direction="out"/>
+    </method>
+    <signal name="Picked">
+      <arg type="s" name="emoji"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+/// A D-Bus error reply: the error name and a message.
+type MethodError = (&'static str, String);
+
+/// Export the picker interface on `connection`, backed by `controller`.
+///
+/// `on_show` is called for `Show` with the requested query. Every emoji the controller
+/// records as picked from now on is announced with the `Picked` signal.
+pub fn export<F: Fn(&str) + 'static>(
+    connection: &gio::DBusConnection,
+    controller: Rc<RefCell<EmojiPickerController>>,
+    on_show: F,
+) -> Result<gio::RegistrationId, glib::Error> {
+    let node = gio::DBusNodeInfo::for_xml(INTERFACE_XML)?;
+    let interface = node.lookup_interface(INTERFACE_NAME).expect("interface is defined in INTERFACE_XML");
+    let registration = connection
+        .register_object(OBJECT_PATH, &interface)
+        .method_call({
+            let controller = controller.clone();
+            move |_connection, _sender, _path, _interface, method, parameters, invocation| {
+                match handle_method(&controller, &on_show, method, &parameters) {
+                    Ok(reply) => invocation.return_value(reply.as_ref()),
+                    Err((name, message)) => invocation.return_dbus_error(name, &message),
+                }
+            }
+        })
+        .build()?;
+    let signal_connection = connection.clone();
+    controller.borrow_mut().add_pick_listener(move |ch| emit_picked(&signal_connection, ch));
+    Ok(registration)
+}
+
+/// Announce that `ch` was picked.
+pub fn emit_picked(connection: &gio::DBusConnection, ch: &str) {
+    let parameters = (ch,).to_variant();
+    if let Err(err) = connection.emit_signal(None, OBJECT_PATH, INTERFACE_NAME, "Picked", Some(&parameters)) {
+        log::warn!("Could not emit Picked for {}: {}", ch, err);
+    }
+}
+
+fn handle_method(
+    controller: &RefCell<EmojiPickerController>,
+    on_show: &dyn Fn(&str),
+    method: &str,
+    parameters: &glib::Variant,
+) -> Result<Option<glib::Variant>, MethodError> {
+    let (argument,) = parameters
+        .get::<(String,)>()
+        .ok_or_else(|| (INVALID_ARGS_ERROR, format!("{} expects a single string", method)))?;
+    log::info!("D-Bus call {}('{}')", method, argument);
+    match method {
+        "Show" => {
+            // The controller must not be borrowed here: showing the query runs a search
+            on_show(&argument);
+            Ok(None)
+        }
+        "Search" => {
+            let controller = controller.borrow();
+            let results: Vec<(String, String)> = controller
+                .rank(&argument)
+                .iter()
+                .map(|e| (e.ch.to_string(), e.name(&controller.locales).to_string()))
+                .collect();
+            Ok(Some((results,).to_variant()))
+        }
+        "Lookup" => {
+            let controller = controller.borrow();
+            let emoji = controller
+                .lookup(&argument)
+                .ok_or_else(|| (NOT_FOUND_ERROR, format!("'{}' is not a known emoji", argument)))?;
+            Ok(Some(glib::Variant::tuple_from_iter([metadata(emoji, &controller.locales)])))
+        }
+        _ => Err((UNKNOWN_METHOD_ERROR, format!("No method {} on {}", method, INTERFACE_NAME))),
+    }
+}
+
+/// The `Lookup` reply for `emoji`, with its name and keywords in the first of `locales` it has.
+fn metadata(emoji: &Emoji, locales: &[&str]) -> glib::Variant {
+    let variants: Vec<&str> = emoji.skin_tone_variants.map_or_else(Vec::new, |v| v.to_vec());
+    let dict = glib::VariantDict::new(None);
+    dict.insert_value("emoji", &emoji.ch.to_variant());
+    dict.insert_value("name", &emoji.name(locales).to_variant());
+    dict.insert_value("category", &emoji.category.to_variant());
+    dict.insert_value("keywords", &emoji.keywords(locales).to_variant());
+    dict.insert_value("skin-tone-variants", &variants.to_variant());
+    dict.end()
+}
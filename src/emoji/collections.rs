@@ -0,0 +1,181 @@
+//! User-defined snippets, stored as TOML in `$XDG_CONFIG_HOME/emoji-picker/collections.toml`.
+//!
+//! Each snippet is a piece of text with a name, keywords and a category, picked like any emoji:
+//!
+//! ```toml
+//! [[snippet]]
+//! text = "¯\\_(ツ)_/¯"
+//! name = "shrug"
+//! keywords = ["dunno", "whatever"]
+//!
+//! [[snippet]]
+//! text = "LGTM ✅"
+//! name = "looks good to me"
+//! category = "Review"
+//! ```
+//!
+//! Snippets without a category go to [`CUSTOM_CATEGORY`]. They are appended to the built-in
+//! `EMOJIS` and `KAOMOJI` at startup (see [`all_emojis`]), so `EMOJIS` keeps its search index.
+
+use crate::emoji::emoji_data::{Emoji, Localized, Status, EMOJIS};
+use crate::emoji::kaomoji::KAOMOJI;
+use crate::emoji::locale::FALLBACK_LOCALE;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name of the collections inside the config directory.
+pub const COLLECTIONS_FILE: &str = "collections.toml";
+
+/// Category of snippets that do not name one.
+pub const CUSTOM_CATEGORY: &str = "Custom";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CollectionsFile {
+    #[serde(default, rename = "snippet")]
+    snippets: Vec<Snippet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Snippet {
+    text: String,
+    name: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    category: Option<String>,
+}
+
+/// Leak `text` for the `'static` fields of [`Emoji`]; snippets live as long as the process.
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+impl Snippet {
+    /// The snippet as an emoji. Its name and keywords are stored under [`FALLBACK_LOCALE`],
+    /// which every locale chain ends in, so they are searched and shown in any locale.
+    fn into_emoji(self) -> Emoji {
+        let keywords: Vec<&'static str> = self.keywords.into_iter().map(leak).collect();
+        let localized = vec![Localized {
+            locale: FALLBACK_LOCALE,
+            name: leak(self.name),
+            keywords: Box::leak(keywords.into_boxed_slice()),
+        }];
+        let category = self.category.filter(|c| !c.trim().is_empty());
+        Emoji {
+            ch: leak(self.text),
+            localized: Box::leak(localized.into_boxed_slice()),
+            category: category.map_or(CUSTOM_CATEGORY, leak),
+            subgroup: "",
+            version: (0, 0),
+            status: Status::FullyQualified,
+            skin_tone_variants: None,
+        }
+    }
+}
+
+/// Parse snippets from TOML text, skipping those without text.
+///
+/// Snippets spanning several lines (or containing tabs) are skipped too: like emoji, they are
+/// stored one per line in the usage history and listed one per line by `--dmenu`.
+pub fn parse(text: &str) -> Result<Vec<Emoji>, toml::de::Error> {
+    let file: CollectionsFile = toml::from_str(text)?;
+    let usable = |snippet: &Snippet| {
+        let single_line = !snippet.text.contains(['\n', '\r', '\t']);
+        if !single_line {
+            log::warn!("Skipping snippet '{}': it must fit on one line", snippet.name);
+        }
+        single_line && !snippet.text.is_empty()
+    };
+    Ok(file.snippets.into_iter().filter(usable).map(Snippet::into_emoji).collect())
+}
+
+/// Load snippets from `path`. A missing file has none; an invalid one is logged and ignored.
+pub fn load(path: &Path) -> Vec<Emoji> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse(&text).unwrap_or_else(|err| {
+            log::warn!("Ignoring invalid collections {}: {}", path.display(), err);
+            Vec::new()
+        }),
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("Could not read collections {}: {}", path.display(), err);
+            }
+            Vec::new()
+        }
+    }
+}
+
+/// Default location of the collections file: `$XDG_CONFIG_HOME/emoji-picker/collections.toml`.
+pub fn default_path() -> PathBuf {
+    crate::paths::config_dir().join(COLLECTIONS_FILE)
+}
+
+/// `EMOJIS` and `KAOMOJI`, followed by the user's snippets from [`default_path`].
+pub fn all_emojis() -> Vec<Emoji> {
+    let mut emojis = EMOJIS.to_vec();
+    emojis.extend_from_slice(KAOMOJI);
+    emojis.extend(load(&default_path()));
+    emojis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNIPPETS: &str = r#"
+[[snippet]]
+text = "¯\\_(ツ)_/¯"
+name = "shrug"
+keywords = ["dunno", "whatever"]
+
+[[snippet]]
+text = "LGTM ✅"
+name = "looks good to me"
+category = "Review"
+
+[[snippet]]
+text = ""
+name = "nothing"
+
+[[snippet]]
+text = """
+two
+lines"""
+name = "multi-line"
+"#;
+
+    #[test]
+    fn test_parse_snippets() {
+        let snippets = parse(SNIPPETS).unwrap();
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].ch, "¯\\_(ツ)_/¯");
+        assert_eq!(snippets[0].category, CUSTOM_CATEGORY);
+        assert_eq!(snippets[0].name(&["nl"]), "shrug");
+        assert_eq!(snippets[0].keywords(&["en"]), ["dunno", "whatever"]);
+        assert_eq!(snippets[1].category, "Review");
+        assert!(snippets[1].keywords(&["en"]).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_collections() {
+        assert!(parse("").unwrap().is_empty());
+        assert!(parse("[[snippet]]\nname = \"no text\"\n").is_err());
+        assert!(parse("[[snippet]]\ntext = \"x\"\nname = \"x\"\ncolour = \"red\"\n").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_and_invalid_files() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-collections-{}", std::process::id()));
+        let path = dir.join(COLLECTIONS_FILE);
+        assert!(load(&path).is_empty());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "snippet = 3").unwrap();
+        assert!(load(&path).is_empty());
+        fs::write(&path, SNIPPETS).unwrap();
+        assert_eq!(load(&path).len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
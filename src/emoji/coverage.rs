@@ -0,0 +1,194 @@
+//! Which emoji the installed fonts can show, so those they cannot are left out of the grids
+//! and the search instead of showing as empty boxes.
+//!
+//! The check itself needs the fonts (see `ui::coverage`); this module keeps its result. As
+//! checking every emoji takes a while, the result is cached in
+//! `$XDG_CACHE_HOME/emoji-picker/coverage-<key>.txt`, one unsupported emoji per line. The key
+//! is a hash of the font configuration and of the emoji checked, so installing or updating
+//! fonts, or a new emoji set, leads to a new check. The coverages of earlier keys are then
+//! removed.
+
+use crate::emoji::emoji_data::EMOJIS;
+use std::collections::HashSet;
+use std::iter;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "# emoji-picker coverage v2";
+
+/// Start and end of the cached coverage file names, around the key.
+const CACHE_PREFIX: &str = "coverage-";
+const CACHE_SUFFIX: &str = ".txt";
+
+/// The emoji of `EMOJIS` and their skin tones that the fonts cannot show. Anything else,
+/// including kaomoji and snippets, counts as supported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    unsupported: HashSet<String>,
+}
+
+impl Coverage {
+    /// The coverage in which the fonts cannot show `unsupported`.
+    pub fn from_unsupported<'a>(unsupported: impl IntoIterator<Item = &'a str>) -> Self {
+        Self { unsupported: unsupported.into_iter().map(str::to_string).collect() }
+    }
+
+    /// Whether the fonts can show `ch`.
+    pub fn is_supported(&self, ch: &str) -> bool {
+        !self.unsupported.contains(ch)
+    }
+
+    /// Number of emoji and skin toned emoji the fonts cannot show.
+    pub fn unsupported_count(&self) -> usize {
+        self.unsupported.len()
+    }
+
+    /// Parse a coverage from its on-disk text form, one unsupported emoji per line.
+    pub fn parse(text: &str) -> Self {
+        let unsupported = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Self { unsupported }
+    }
+
+    /// Serialize the coverage to its on-disk text form, in the order of [`texts`].
+    pub fn to_text(&self) -> String {
+        let mut out = String::from(HEADER);
+        out.push('\n');
+        for text in texts().filter(|text| self.unsupported.contains(*text)) {
+            out.push_str(text);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Load a coverage from `path`, or `None` if there is none yet or it cannot be read.
+    pub fn load(path: &Path) -> Option<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Some(Self::parse(&text)),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Could not read font coverage {}: {}", path.display(), err);
+                }
+                None
+            }
+        }
+    }
+
+    /// Write the coverage to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never leaves a partial coverage behind.
+        let tmp = path.with_extension("txt.tmp");
+        fs::write(&tmp, self.to_text())?;
+        fs::rename(&tmp, path)
+    }
+
+    /// The coverage cached for `font_configuration` in `cache_dir`, if any.
+    pub fn cached(cache_dir: &Path, font_configuration: &str) -> Option<Self> {
+        Self::load(&cache_dir.join(cache_file(font_configuration)))
+    }
+
+    /// Cache the coverage for `font_configuration` in `cache_dir`, replacing those of other
+    /// font configurations.
+    pub fn store(&self, cache_dir: &Path, font_configuration: &str) {
+        let path = cache_dir.join(cache_file(font_configuration));
+        match self.save(&path) {
+            Ok(()) => remove_stale(cache_dir, &path),
+            Err(err) => log::warn!("Could not save font coverage {}: {}", path.display(), err),
+        }
+    }
+}
+
+/// The texts a coverage check tries: each emoji of `EMOJIS`, followed by its skin tones.
+pub fn texts() -> impl Iterator<Item = &'static str> {
+    EMOJIS.iter().flat_map(|e| iter::once(e.ch).chain(e.skin_tone_variants.into_iter().flatten()))
+}
+
+/// File name of the cached coverage for `font_configuration` and the current [`texts`].
+pub fn cache_file(font_configuration: &str) -> String {
+    let checked = texts().flat_map(|text| text.bytes().chain([0]));
+    let key = fnv1a(HEADER.bytes().chain(font_configuration.bytes()).chain([0]).chain(checked));
+    format!("{}{:016x}{}", CACHE_PREFIX, key, CACHE_SUFFIX)
+}
+
+/// 64-bit FNV-1a hash of `bytes`. Unlike `DefaultHasher` it is the same in every build, so
+/// a new release of the picker or of Rust does not throw the cached coverage away.
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Remove the cached coverages in `cache_dir` other than `current`.
+fn remove_stale(cache_dir: &Path, current: &Path) {
+    let Ok(entries) = fs::read_dir(cache_dir) else { return };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if path != current && name.starts_with(CACHE_PREFIX) && name.ends_with(CACHE_SUFFIX) {
+            if let Err(err) = fs::remove_file(&path) {
+                log::warn!("Could not remove old font coverage {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+/// Default directory of the cached coverages: `$XDG_CACHE_HOME/emoji-picker`.
+pub fn default_dir() -> PathBuf {
+    crate::paths::cache_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_and_text_round_trip() {
+        let coverage = Coverage::from_unsupported(["🫠", "🇺🇳", "👋🏿"]);
+        assert_eq!(coverage.unsupported_count(), 3);
+        assert!(!coverage.is_supported("🫠"));
+        assert!(!coverage.is_supported("👋🏿"));
+        assert!(coverage.is_supported("👋🏾"));
+        assert!(coverage.is_supported("😀"));
+        assert!(coverage.is_supported("(＾▽＾)"), "anything not in EMOJIS counts as supported");
+        assert_eq!(Coverage::parse(&coverage.to_text()), coverage);
+        assert!(coverage.to_text().starts_with(HEADER));
+    }
+
+    #[test]
+    fn test_texts_include_the_skin_tones() {
+        assert!(texts().count() > EMOJIS.len());
+        assert!(texts().any(|text| text == "👋🏿"));
+    }
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(*b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(*b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(*b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_cache_per_font_configuration() {
+        assert_eq!(cache_file("Noto Color Emoji"), cache_file("Noto Color Emoji"));
+        assert_ne!(cache_file("Noto Color Emoji"), cache_file("Twemoji Mozilla"));
+
+        let dir = std::env::temp_dir().join(format!("emoji-picker-coverage-{}", std::process::id()));
+        assert_eq!(Coverage::cached(&dir, "fonts"), None);
+        let coverage = Coverage::from_unsupported(["🫠", "👋🏿"]);
+        coverage.store(&dir, "fonts");
+        assert_eq!(Coverage::cached(&dir, "fonts"), Some(coverage));
+        assert_eq!(Coverage::cached(&dir, "other fonts"), None);
+        Coverage::default().store(&dir, "other fonts");
+        assert_eq!(Coverage::cached(&dir, "other fonts"), Some(Coverage::default()));
+        assert!(!dir.join(cache_file("fonts")).exists(), "the coverage of the old fonts is removed");
+        assert!(dir.join(cache_file("other fonts")).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+//! Hand-picked favorite emoji, pinned in an order of the user's choosing.
+//!
+//! Unlike the usage history, favorites never age out: they stay until unpinned. They are
+//! stored one per line in `$XDG_DATA_HOME/emoji-picker/favorites.txt`, in their order, so the
+//! file can be edited by hand.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name of the favorites inside the data directory.
+pub const FAVORITES_FILE: &str = "favorites.txt";
+
+const HEADER: &str = "# emoji-picker favorites v1";
+
+/// Pinned emoji, in the order they are shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Favorites {
+    entries: Vec<String>,
+}
+
+impl Favorites {
+    /// All favorites, in order.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Whether `ch` is pinned.
+    pub fn contains(&self, ch: &str) -> bool {
+        self.entries.iter().any(|e| e == ch)
+    }
+
+    /// Whether nothing is pinned.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of pinned emoji.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Pin `ch` after the current favorites. Returns false if it was pinned already.
+    pub fn pin(&mut self, ch: &str) -> bool {
+        if self.contains(ch) {
+            return false;
+        }
+        self.entries.push(ch.to_string());
+        true
+    }
+
+    /// Unpin `ch`. Returns false if it was not pinned.
+    pub fn unpin(&mut self, ch: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e != ch);
+        self.entries.len() != before
+    }
+
+    /// Pin `ch` if it is not pinned, unpin it otherwise. Returns whether it is pinned now.
+    pub fn toggle(&mut self, ch: &str) -> bool {
+        !self.unpin(ch) && self.pin(ch)
+    }
+
+    /// Move `ch` by `offset` places (negative is towards the front), stopping at either end.
+    /// Returns its new position, or `None` if it is not pinned.
+    pub fn move_by(&mut self, ch: &str, offset: isize) -> Option<usize> {
+        let from = self.entries.iter().position(|e| e == ch)?;
+        let to = from.saturating_add_signed(offset).min(self.entries.len() - 1);
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        Some(to)
+    }
+
+    /// Parse favorites from their on-disk text form, one per line. Duplicates are dropped.
+    pub fn parse(text: &str) -> Self {
+        let mut seen = HashSet::new();
+        let entries = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter(|line| seen.insert(line.to_string()))
+            .map(str::to_string)
+            .collect();
+        Self { entries }
+    }
+
+    /// Serialize the favorites to their on-disk text form.
+    pub fn to_text(&self) -> String {
+        let mut out = String::from(HEADER);
+        out.push('\n');
+        for e in &self.entries {
+            out.push_str(e);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Load the favorites from `path`. A missing or unreadable file has none.
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Could not read favorites {}: {}", path.display(), err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the favorites to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never loses the favorites.
+        let tmp = path.with_extension("txt.tmp");
+        fs::write(&tmp, self.to_text())?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// Default location of the favorites file: `$XDG_DATA_HOME/emoji-picker/favorites.txt`.
+pub fn default_path() -> PathBuf {
+    crate::paths::data_dir().join(FAVORITES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(favorites: &Favorites) -> Vec<&str> {
+        favorites.entries().iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_pin_unpin_and_toggle() {
+        let mut favorites = Favorites::default();
+        assert!(favorites.pin("😀"));
+        assert!(favorites.pin("👍"));
+        assert!(!favorites.pin("😀"));
+        assert_eq!(entries(&favorites), vec!["😀", "👍"]);
+        assert!(!favorites.toggle("😀"));
+        assert!(favorites.toggle("🎉"));
+        assert_eq!(entries(&favorites), vec!["👍", "🎉"]);
+        assert!(favorites.unpin("👍"));
+        assert!(!favorites.unpin("👍"));
+        assert_eq!(entries(&favorites), vec!["🎉"]);
+    }
+
+    #[test]
+    fn test_move_stops_at_either_end() {
+        let mut favorites = Favorites::parse("a\nb\nc\n");
+        assert_eq!(favorites.move_by("c", -1), Some(1));
+        assert_eq!(entries(&favorites), vec!["a", "c", "b"]);
+        assert_eq!(favorites.move_by("a", 5), Some(2));
+        assert_eq!(entries(&favorites), vec!["c", "b", "a"]);
+        assert_eq!(favorites.move_by("c", -1), Some(0));
+        assert_eq!(favorites.move_by("x", 1), None);
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let favorites = Favorites::parse("# comment\n❤️\n\n👍🏽\n❤️\n");
+        assert_eq!(entries(&favorites), vec!["❤️", "👍🏽"]);
+        assert_eq!(Favorites::parse(&favorites.to_text()), favorites);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-favorites-{}", std::process::id()));
+        let path = dir.join(FAVORITES_FILE);
+        let mut favorites = Favorites::default();
+        favorites.pin("🎉");
+        favorites.save(&path).unwrap();
+        assert_eq!(Favorites::load(&path), favorites);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(Favorites::load(&path).is_empty());
+    }
+}
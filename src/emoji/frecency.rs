@@ -0,0 +1,84 @@
+//! Frecency scoring: ranks emoji by how often and how recently they were picked.
+//!
+//! All functions here are pure; the current time is always passed in so results are
+//! reproducible in tests.
+
+use crate::emoji::usage::UsageEntry;
+
+/// Age (in seconds) after which a pick counts for half as much: one week.
+pub const HALF_LIFE_SECS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+/// Maximum share of the final score contributed by frecency. The rest comes from
+/// the text match, so a poor match can never beat a perfect one on usage alone.
+pub const FRECENCY_WEIGHT: f64 = 0.35;
+
+/// Raw frecency of an emoji: each pick is worth 1, decaying exponentially with the age of
+/// the last pick. Emoji that were never picked score 0.
+pub fn frecency(count: u32, last_used: u64, now: u64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    let age = now.saturating_sub(last_used) as f64;
+    count as f64 * 0.5f64.powf(age / HALF_LIFE_SECS)
+}
+
+/// Frecency of an optional usage entry, normalized to `0.0..1.0`.
+pub fn normalized(entry: Option<&UsageEntry>, now: u64) -> f64 {
+    match entry {
+        Some(e) => {
+            let f = frecency(e.count, e.last_used, now);
+            f / (f + 1.0)
+        }
+        None => 0.0,
+    }
+}
+
+/// Combine a text match score (`0.0..=1.0`) with a normalized frecency (`0.0..1.0`).
+pub fn combine(match_score: f64, normalized_frecency: f64) -> f64 {
+    (1.0 - FRECENCY_WEIGHT) * match_score + FRECENCY_WEIGHT * normalized_frecency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn entry(count: u32, last_used: u64) -> UsageEntry {
+        UsageEntry {
+            ch: "❤️".to_string(),
+            count,
+            last_used,
+        }
+    }
+
+    #[test]
+    fn test_frecency_grows_with_count_and_decays_with_age() {
+        let now = 100 * DAY;
+        assert_eq!(frecency(0, now, now), 0.0);
+        assert!(frecency(5, now, now) > frecency(1, now, now));
+        assert!(frecency(5, now, now) > frecency(5, now - DAY, now));
+        let half = frecency(4, now - 7 * DAY, now);
+        assert!((half - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_normalized_is_bounded() {
+        let now = 10 * DAY;
+        assert_eq!(normalized(None, now), 0.0);
+        let n = normalized(Some(&entry(u32::MAX, now)), now);
+        assert!(n > 0.99 && n < 1.0);
+        // A pick "in the future" (clock skew) is treated as a pick right now.
+        assert_eq!(normalized(Some(&entry(1, now + DAY)), now), 0.5);
+    }
+
+    #[test]
+    fn test_combine_prefers_used_emoji_among_equal_matches() {
+        let now = 10 * DAY;
+        let used = combine(0.8, normalized(Some(&entry(10, now)), now));
+        let unused = combine(0.8, normalized(None, now));
+        assert!(used > unused);
+        // Usage alone cannot lift a weak match above a perfect one.
+        assert!(combine(0.3, 0.999) < combine(1.0, 0.0));
+    }
+}
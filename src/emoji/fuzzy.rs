@@ -0,0 +1,295 @@
+//! Fuzzy text matching with scores, used to rank search results.
+//!
+//! A query matches a text in one of these ways, from best to worst:
+//!
+//! | match                                    | score      |
+//! |------------------------------------------|------------|
+//! | exact                                    | 1.0        |
+//! | prefix of the text                       | 0.9        |
+//! | a whole word in the text                 | 0.85       |
+//! | prefix of a word in the text             | 0.8        |
+//! | substring                                | 0.6        |
+//! | a word (or word prefix) within 1–2 typos | 0.5, 0.4   |
+//! | subsequence starting at a word           | 0.2 – 0.45 |
+//!
+//! Typos are only looked for in words that start like the query: with the same letter, or
+//! with its first two letters swapped. A subsequence may start at any word, including one
+//! after a hyphen (`rex` in `t-rex`).
+//!
+//! Both the query and the text are expected to be lowercase already.
+
+/// Score for a match with one typo; every further typo costs [`TYPO_PENALTY`].
+const TYPO_SCORE: f64 = 0.5;
+const TYPO_PENALTY: f64 = 0.1;
+
+/// Score for a query found anywhere inside a text.
+pub const SUBSTRING_SCORE: f64 = 0.6;
+
+/// Queries shorter than this only match exactly, by prefix or as substring.
+pub const MIN_FUZZY_LEN: usize = 3;
+/// Queries shorter than this get no typo tolerance.
+const MIN_TYPO_LEN: usize = 4;
+
+/// Score how well the lowercase `query` matches the lowercase `text`, from 0.0 to 1.0.
+///
+/// Returns `None` if the query does not match at all. To match one query against many texts,
+/// prepare a [`Matcher`] once instead.
+pub fn score(query: &str, text: &str) -> Option<f64> {
+    Matcher::new(query).score(text)
+}
+
+/// Smallest number of typos between `query` and any word (or word prefix) of `text` that
+/// starts like it (see [`starts_alike`]), if it is within the tolerance for the query length.
+pub fn typo_distance(query: &str, text: &str) -> Option<usize> {
+    Matcher::new(query).typo_distance(text)
+}
+
+/// Score `query` as a subsequence of `text` (e.g. "thmbup" in "thumbs up").
+///
+/// The match must start at the beginning of a word. Compact matches and matches that hit
+/// many word starts score higher. Spaces in the query are ignored.
+pub fn subsequence_score(query: &str, text: &str) -> Option<f64> {
+    Matcher::new(query).subsequence_score(text)
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions) between
+/// `a` and `b`, or `None` if it exceeds `max`.
+pub fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    edit_distance_with(a, b, max, &mut Scratch::default())
+}
+
+/// A query prepared for matching against many texts.
+///
+/// Keeps the decoded query and scratch buffers around, so scoring a text does not allocate.
+pub struct Matcher {
+    query: String,
+    chars: Vec<char>,
+    /// The query without whitespace, for subsequence matching.
+    compact: Vec<char>,
+    max_typos: usize,
+    scratch: Scratch,
+    text: Vec<char>,
+}
+
+/// Reusable rows for [`edit_distance_with`].
+#[derive(Default)]
+struct Scratch {
+    prev2: Vec<usize>,
+    prev: Vec<usize>,
+    cur: Vec<usize>,
+}
+
+impl Matcher {
+    pub fn new(query: &str) -> Self {
+        let chars: Vec<char> = query.chars().collect();
+        let compact = chars.iter().copied().filter(|c| !c.is_whitespace()).collect();
+        let max_typos = if chars.len() >= 8 { 2 } else { 1 };
+        Self {
+            query: query.to_string(),
+            chars,
+            compact,
+            max_typos,
+            scratch: Scratch::default(),
+            text: Vec::new(),
+        }
+    }
+
+    /// The query this matcher was prepared for.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// See [`score`].
+    pub fn score(&mut self, text: &str) -> Option<f64> {
+        let query = self.query.as_str();
+        if query.is_empty() {
+            return None;
+        }
+        if text == query {
+            Some(1.0)
+        } else if text.starts_with(query) {
+            Some(0.9)
+        } else if text.split_whitespace().any(|w| w == query) {
+            Some(0.85)
+        } else if text.split_whitespace().any(|w| w.starts_with(query)) {
+            Some(0.8)
+        } else if text.contains(query) {
+            Some(SUBSTRING_SCORE)
+        } else if let Some(typos) = self.typo_distance(text) {
+            Some(TYPO_SCORE - TYPO_PENALTY * (typos - 1) as f64)
+        } else {
+            self.subsequence_score(text)
+        }
+    }
+
+    /// See [`typo_distance`].
+    pub fn typo_distance(&mut self, text: &str) -> Option<usize> {
+        let q = &self.chars;
+        if q.len() < MIN_TYPO_LEN {
+            return None;
+        }
+        let max = self.max_typos;
+        let w = &mut self.text;
+        let scratch = &mut self.scratch;
+        std::iter::once(text)
+            .chain(text.split_whitespace())
+            .filter_map(|word| {
+                w.clear();
+                w.extend(word.chars());
+                if w.len() + max < q.len() || !starts_alike(q, w) {
+                    return None;
+                }
+                let full = edit_distance_with(q, w, max, scratch);
+                // Also compare against the start of longer words, so typos while typing still match.
+                let prefix = if w.len() > q.len() {
+                    edit_distance_with(q, &w[..q.len()], max, scratch)
+                } else {
+                    None
+                };
+                match (full, prefix) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                }
+            })
+            .min()
+    }
+
+    /// See [`subsequence_score`].
+    pub fn subsequence_score(&mut self, text: &str) -> Option<f64> {
+        let q = &self.compact;
+        if q.len() < MIN_FUZZY_LEN || !text.contains(q[0]) {
+            return None;
+        }
+        let t = &mut self.text;
+        t.clear();
+        t.extend(text.chars());
+        let t = &*t;
+        let is_word_start = |i: usize| i == 0 || t[i - 1].is_whitespace() || t[i - 1] == '-';
+        (0..t.len())
+            .filter(|&start| t[start] == q[0] && is_word_start(start))
+            .filter_map(|start| {
+                let mut first = None;
+                let mut last = 0;
+                let mut word_starts = 0;
+                let mut qi = 0;
+                for (i, &c) in t.iter().enumerate().skip(start) {
+                    if qi < q.len() && c == q[qi] {
+                        first.get_or_insert(i);
+                        last = i;
+                        word_starts += usize::from(is_word_start(i));
+                        qi += 1;
+                    }
+                }
+                if qi < q.len() {
+                    return None;
+                }
+                let span = last - first? + 1;
+                let compactness = q.len() as f64 / span as f64;
+                let starts = word_starts as f64 / q.len() as f64;
+                Some(0.2 + 0.15 * compactness + 0.1 * starts)
+            })
+            .max_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// Whether `word` starts like `query`: with the same character, or with its first two
+/// characters swapped. Typos are only looked for in such words, so the search index can find
+/// them by prefix.
+pub fn starts_alike(query: &[char], word: &[char]) -> bool {
+    match (query, word) {
+        ([q0, ..], [w0, ..]) if q0 == w0 => true,
+        ([q0, q1, ..], [w0, w1, ..]) => q0 == w1 && q1 == w0,
+        _ => false,
+    }
+}
+
+fn edit_distance_with(a: &[char], b: &[char], max: usize, scratch: &mut Scratch) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max || !shares_leading_char(a, b, max) {
+        return None;
+    }
+    let Scratch { prev2, prev, cur } = scratch;
+    let width = b.len() + 1;
+    prev2.clear();
+    prev2.resize(width, 0);
+    prev.clear();
+    prev.extend(0..width);
+    cur.clear();
+    cur.resize(width, 0);
+    for i in 1..=a.len() {
+        cur[0] = i;
+        let mut row_min = cur[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut d = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(prev2[j - 2] + 1);
+            }
+            cur[j] = d;
+            row_min = row_min.min(d);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(prev2, prev);
+        std::mem::swap(prev, cur);
+    }
+    Some(prev[b.len()]).filter(|&d| d <= max)
+}
+
+/// Cheap necessary condition for `a` and `b` to be within `max` edits: one of the first
+/// `max + 1` characters of `a` survives the edits, shifted by at most `max` positions.
+fn shares_leading_char(a: &[char], b: &[char], max: usize) -> bool {
+    let head = &b[..b.len().min(2 * max + 1)];
+    a.iter().take(max + 1).any(|c| head.contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_score_tiers() {
+        assert_eq!(score("red heart", "red heart"), Some(1.0));
+        assert_eq!(score("red", "red heart"), Some(0.9));
+        assert_eq!(score("heart", "red heart"), Some(0.85));
+        assert_eq!(score("hea", "red heart"), Some(0.8));
+        assert_eq!(score("eart", "red heart"), Some(0.6));
+        assert_eq!(score("haert", "red heart"), Some(0.5));
+        assert!(score("rdhrt", "red heart").unwrap() < 0.5);
+        assert_eq!(score("xyz", "red heart"), None);
+        assert_eq!(score("", "red heart"), None);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance(&chars("smiel"), &chars("smile"), 2), Some(1));
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting"), 3), Some(3));
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting"), 2), None);
+        assert_eq!(edit_distance(&chars("abc"), &chars("abc"), 0), Some(0));
+    }
+
+    #[test]
+    fn test_typos_match_words_and_word_prefixes() {
+        assert_eq!(typo_distance("smiel", "big smile"), Some(1));
+        assert_eq!(typo_distance("smiel", "smile"), Some(1));
+        assert_eq!(typo_distance("raibow", "rainbow flag"), Some(1));
+        assert_eq!(typo_distance("cat", "act"), None);
+        assert_eq!(typo_distance("smiel", "frowning face"), None);
+        assert_eq!(typo_distance("msile", "smile"), Some(1));
+        assert_eq!(typo_distance("amile", "smile"), None, "the first letter is not a typo");
+    }
+
+    #[test]
+    fn test_subsequence_needs_word_start_and_prefers_compact_matches() {
+        assert!(subsequence_score("thmbup", "thumbs up").is_some());
+        assert!(subsequence_score("humbs", "thumbs up").is_none());
+        assert!(subsequence_score("tu", "thumbs up").is_none());
+        let compact = subsequence_score("thmbup", "thumbs up").unwrap();
+        let spread = subsequence_score("thmbup", "thumbtack pushpin").unwrap();
+        assert!(compact > spread);
+    }
+}
@@ -0,0 +1,179 @@
+//! Lookups in the precomputed search index generated by build.rs (see `search_index`).
+//!
+//! The index holds the lowercased names and keywords of every emoji in `EMOJIS` and a sorted
+//! table of all distinct words (see [`index_words`]). Search uses the word table to narrow a query down to candidate
+//! emoji and then scores only those, on texts that are already normalized.
+
+use crate::emoji::emoji_data::{Emoji, EMOJIS};
+use crate::emoji::fuzzy::{self, Matcher};
+use crate::emoji::search_index::{SearchTexts, SEARCH_TEXTS, TOKENS};
+
+/// Whether `emojis` starts with the built-in `EMOJIS` table, so index positions can be used
+/// for that part.
+pub fn covers(emojis: &[Emoji]) -> bool {
+    emojis.len() >= EMOJIS.len() && emojis.iter().zip(EMOJIS.iter()).all(|(a, b)| a.ch == b.ch)
+}
+
+/// The normalized search texts of `EMOJIS[index]`.
+pub fn texts(index: usize) -> &'static SearchTexts {
+    &SEARCH_TEXTS[index]
+}
+
+/// Bit set of the characters in `text`: one bit per ASCII letter, the rest hashed into the
+/// remaining six bits. Must match `letter_mask` in build.rs, which fills `SearchTexts::letters`.
+pub fn letter_mask(text: &str) -> u32 {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .fold(0, |mask, c| {
+            let bit = if c.is_ascii_lowercase() { c as u32 - 'a' as u32 } else { 26 + c as u32 % 6 };
+            mask | 1 << bit
+        })
+}
+
+/// The words of `text` in the token table: each whitespace-separated word, and the parts of
+/// hyphenated words, as [`fuzzy`] starts words after a hyphen too. Must match `index_words`
+/// in build.rs, which fills `TOKENS`.
+pub fn index_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .flat_map(|word| std::iter::once(word).chain(word.split('-').filter(move |part| !part.is_empty() && *part != word)))
+}
+
+/// Search terms from the subgroup of an emoji, such as `mammal` for `animal-mammal`, matched
+/// as English keywords. Must match `subgroup_terms` in build.rs, which adds them to the English
+/// keywords in `SEARCH_TEXTS`.
+pub fn subgroup_terms(subgroup: &str) -> impl Iterator<Item = &str> {
+    subgroup.split(['-', ' ', '&']).filter(|t| !t.is_empty() && *t != "other")
+}
+
+/// All tokens starting with `prefix`, found by binary search in the sorted token table.
+pub fn tokens_with_prefix(prefix: &str) -> &'static [(&'static str, &'static [u16])] {
+    let start = TOKENS.partition_point(|(token, _)| *token < prefix);
+    let len = TOKENS[start..].partition_point(|(token, _)| token.starts_with(prefix));
+    &TOKENS[start..start + len]
+}
+
+/// Indices into `EMOJIS` of the emoji that may match the lowercase query `q`, in table order.
+///
+/// Every word of the query has to match, so this is the intersection of the candidates of
+/// each word, plus emoji whose character contains the query.
+pub fn candidates(q: &str) -> Vec<usize> {
+    let mut hit: Option<Vec<bool>> = None;
+    for word in q.split_whitespace() {
+        let word_hit = word_candidates(word);
+        hit = Some(match hit {
+            Some(hit) => hit.iter().zip(word_hit).map(|(&a, b)| a && b).collect(),
+            None => word_hit,
+        });
+    }
+    let mut hit = hit.unwrap_or_else(|| vec![false; EMOJIS.len()]);
+    // Queries may also be (part of) the emoji itself
+    for (i, e) in EMOJIS.iter().enumerate() {
+        if !q.is_empty() && e.ch.contains(q) {
+            hit[i] = true;
+        }
+    }
+    hit.iter().enumerate().filter(|(_, &h)| h).map(|(i, _)| i).collect()
+}
+
+/// Flags (by index into `EMOJIS`) of the emoji that may match the single query word `word`.
+///
+/// This is a superset of the emoji that [`fuzzy::score`] accepts for `word`: a text matches
+/// only if one of its words contains `word`, is a typo of it (and so starts like it, see
+/// [`fuzzy::starts_alike`]), or (for subsequence matches) starts with its first letter and
+/// uses all its letters.
+fn word_candidates(word: &str) -> Vec<bool> {
+    let mut hit = vec![false; EMOJIS.len()];
+    let mut mark = |postings: &[u16]| {
+        for &i in postings {
+            hit[i as usize] = true;
+        }
+    };
+    // Subsequence matches start at a token beginning with the first letter of the word,
+    // which is a contiguous range of the sorted token table.
+    let chars: Vec<char> = word.chars().collect();
+    if chars.len() >= fuzzy::MIN_FUZZY_LEN {
+        let letters = letter_mask(word);
+        for (_, postings) in tokens_with_prefix(chars[0].encode_utf8(&mut [0; 4])) {
+            let has_letters = |i: &&u16| SEARCH_TEXTS[**i as usize].letters & letters == letters;
+            let postings: Vec<u16> = postings.iter().filter(has_letters).copied().collect();
+            mark(&postings);
+        }
+    }
+    // Typos start like the word: with its first letter, or its first two letters swapped
+    let mut matcher = Matcher::new(word);
+    let swapped: Option<String> = (chars.len() > 1).then(|| [chars[1], chars[0]].iter().collect());
+    let starts = chars.first().map(|c| c.to_string()).into_iter().chain(swapped);
+    for start in starts {
+        for (token, postings) in tokens_with_prefix(&start) {
+            if matcher.typo_distance(token).is_some() {
+                mark(postings);
+            }
+        }
+    }
+    // Every other match needs a token that contains the word
+    for (token, postings) in TOKENS {
+        if token.contains(word) {
+            mark(postings);
+        }
+    }
+    hit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_is_parallel_to_emojis_and_sorted() {
+        assert_eq!(SEARCH_TEXTS.len(), EMOJIS.len());
+        assert!(TOKENS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(covers(EMOJIS));
+        assert!(!covers(&EMOJIS[1..]));
+        let mut with_snippet = EMOJIS.to_vec();
+        with_snippet.push(EMOJIS[0].clone());
+        assert!(covers(&with_snippet));
+    }
+
+    #[test]
+    fn test_tokens_with_prefix() {
+        let tokens: Vec<_> = tokens_with_prefix("unicor").iter().map(|(t, _)| *t).collect();
+        assert!(tokens.contains(&"unicorn"));
+        assert!(tokens.iter().all(|t| t.starts_with("unicor")));
+        assert!(tokens_with_prefix("zzzzzz").is_empty());
+    }
+
+    #[test]
+    fn test_hyphenated_words_are_also_indexed_by_part() {
+        assert_eq!(index_words("t-rex x").collect::<Vec<_>>(), ["t-rex", "t", "rex", "x"]);
+        // "eed" is a subsequence of "bright-eyed" from the word after the hyphen
+        let child = EMOJIS.iter().position(|e| e.ch == "🧒").unwrap();
+        assert!(candidates("eed").contains(&child));
+    }
+
+    #[test]
+    fn test_subgroups_are_searched_in_english() {
+        let terms: Vec<_> = subgroup_terms("sky & weather").collect();
+        assert_eq!(terms, ["sky", "weather"]);
+        assert_eq!(subgroup_terms("plant-other").collect::<Vec<_>>(), ["plant"]);
+        let monkey = EMOJIS.iter().position(|e| e.ch == "🐒").unwrap();
+        assert_eq!(EMOJIS[monkey].subgroup, "animal-mammal");
+        assert!(candidates("mammal").contains(&monkey));
+    }
+
+    #[test]
+    fn test_candidates_include_typos_and_subsequences() {
+        let unicorn = EMOJIS.iter().position(|e| e.ch == "🦄").unwrap();
+        let thumbs_up = EMOJIS.iter().position(|e| e.ch == "👍").unwrap();
+        assert!(candidates("unicron").contains(&unicorn));
+        assert!(candidates("thmbup").contains(&thumbs_up));
+        assert!(candidates("🦄").contains(&unicorn));
+        assert!(candidates("unicorn").len() < EMOJIS.len() / 4);
+        // Multi-word queries need every word to match
+        let red_heart = EMOJIS.iter().position(|e| e.ch == "❤️").unwrap();
+        let heart = candidates("heart");
+        let heart_red = candidates("heart red");
+        assert!(heart_red.contains(&red_heart));
+        assert!(heart_red.len() < heart.len());
+        assert!(heart_red.iter().all(|i| heart.contains(i)));
+    }
+}
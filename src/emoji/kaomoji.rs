@@ -0,0 +1,48 @@
+//! Kaomoji and text emoticons such as `(╯°□°)╯︵ ┻━┻`, a second built-in dataset next to
+//! `EMOJIS`.
+//!
+//! [`KAOMOJI`] is generated by build.rs from the vendored `data/kaomoji.tsv`, with English
+//! names and keywords, and searched along with the emoji.
+
+pub use crate::emoji::kaomoji_data::KAOMOJI;
+
+use crate::emoji::emoji_data::Emoji;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Category of all kaomoji.
+pub const KAOMOJI_CATEGORY: &str = "Kaomoji";
+
+impl Emoji {
+    /// Whether this is text wider than a single emoji, like a kaomoji or a longer snippet.
+    pub fn is_wide(&self) -> bool {
+        self.ch.graphemes(true).nth(1).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::emoji_data::EMOJIS;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_kaomoji_table() {
+        assert!(!KAOMOJI.is_empty());
+        let mut seen = HashSet::new();
+        for kaomoji in KAOMOJI {
+            assert_eq!(kaomoji.category, KAOMOJI_CATEGORY);
+            assert!(seen.insert(kaomoji.ch), "{} is listed twice", kaomoji.ch);
+            assert!(!kaomoji.name(&["nl"]).is_empty());
+            assert!(!kaomoji.ch.contains(['\t', '\n']));
+        }
+        let shrug = KAOMOJI.iter().find(|k| k.ch == "¯\\_(ツ)_/¯").unwrap();
+        assert_eq!(shrug.name(&["en"]), "shrug");
+        assert!(shrug.keywords(&["en"]).contains(&"dunno"));
+    }
+
+    #[test]
+    fn test_kaomoji_are_wide_and_emoji_are_not() {
+        assert!(KAOMOJI.iter().all(Emoji::is_wide));
+        assert!(EMOJIS.iter().all(|e| !e.is_wide()), "{:?}", EMOJIS.iter().find(|e| e.is_wide()).map(|e| e.ch));
+    }
+}
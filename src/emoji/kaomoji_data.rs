@@ -0,0 +1,4 @@
+//! The kaomoji, generated by build.rs into `OUT_DIR` from the vendored `data/kaomoji.tsv`
+//! (see `kaomoji`).
+
+include!(concat!(env!("OUT_DIR"), "/kaomoji_data.rs"));
@@ -0,0 +1,113 @@
+//! Choosing which of the compiled-in locales to search and display.
+//!
+//! The locales in `LOCALES` are fixed at build time (see `EMOJI_PICKER_LOCALES` in build.rs).
+//! At runtime the user's preference comes from the same environment variables gettext uses:
+//! the colon-separated `LANGUAGE` list first, then `LC_ALL`, `LC_MESSAGES` and `LANG`. Each
+//! preference falls back from region to language (`pt_BR` → `pt`), and English comes last.
+
+use crate::emoji::emoji_data::{Emoji, Localized, LOCALES};
+use once_cell::sync::Lazy;
+
+/// Locale used when none of the user's locales is compiled in, and for missing names.
+pub const FALLBACK_LOCALE: &str = "en";
+
+static CURRENT: Lazy<Vec<&'static str>> = Lazy::new(from_env);
+
+/// The user's locale chain, best first, read from the environment once per process.
+pub fn current() -> &'static [&'static str] {
+    &CURRENT
+}
+
+/// The user's locale chain according to `LANGUAGE`, `LC_ALL`, `LC_MESSAGES` and `LANG`.
+pub fn from_env() -> Vec<&'static str> {
+    let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let locale = var("LC_ALL").or_else(|| var("LC_MESSAGES")).or_else(|| var("LANG"));
+    let language = var("LANGUAGE");
+    let requested = language.iter().flat_map(|l| l.split(':')).chain(locale.as_deref());
+    resolve(requested)
+}
+
+/// Map requested POSIX locale names (like `de_CH.UTF-8` or `fr`) onto a fallback chain of
+/// compiled-in locales, best first, always ending in [`FALLBACK_LOCALE`].
+pub fn resolve<'a>(requested: impl IntoIterator<Item = &'a str>) -> Vec<&'static str> {
+    let mut chain: Vec<&'static str> = Vec::new();
+    let mut push = |name: &str| {
+        if let Some(&locale) = LOCALES.iter().find(|l| **l == name) {
+            if !chain.contains(&locale) {
+                chain.push(locale);
+            }
+        }
+    };
+    for name in requested {
+        // Strip the encoding and modifier: "sr_RS.UTF-8@latin" -> "sr_RS"
+        let name = name.split(['.', '@']).next().unwrap_or_default().replace('-', "_");
+        if name.is_empty() || name == "C" || name == "POSIX" {
+            continue;
+        }
+        push(&name);
+        if let Some((language, _)) = name.split_once('_') {
+            push(language);
+        }
+    }
+    push(FALLBACK_LOCALE);
+    chain
+}
+
+impl Emoji {
+    /// Name and keywords of this emoji in `locale`, if it has them.
+    pub fn localized_in(&self, locale: &str) -> Option<&'static Localized> {
+        self.localized.iter().find(|l| l.locale == locale)
+    }
+
+    /// The name of this emoji in the first of `locales` it has, or else in [`FALLBACK_LOCALE`].
+    pub fn name(&self, locales: &[&str]) -> &'static str {
+        locales
+            .iter()
+            .chain([&FALLBACK_LOCALE])
+            .find_map(|l| self.localized_in(l))
+            .or(self.localized.first())
+            .map_or("", |l| l.name)
+    }
+
+    /// The keywords of this emoji in the first of `locales` it has, or else in [`FALLBACK_LOCALE`].
+    pub fn keywords(&self, locales: &[&str]) -> &'static [&'static str] {
+        locales
+            .iter()
+            .chain([&FALLBACK_LOCALE])
+            .find_map(|l| self.localized_in(l))
+            .map_or(&[], |l| l.keywords)
+    }
+
+    /// Names and keywords of this emoji in each of `locales` it has.
+    pub fn localized_for<'a>(&'a self, locales: &'a [&str]) -> impl Iterator<Item = &'static Localized> + 'a {
+        self.localized.iter().filter(|l| locales.contains(&l.locale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::emoji_data::EMOJIS;
+
+    #[test]
+    fn test_resolve_falls_back_from_region_to_language_to_english() {
+        assert_eq!(resolve(["nl_BE.UTF-8"]), vec!["nl", "en"]);
+        assert_eq!(resolve(["nl"]), vec!["nl", "en"]);
+        assert_eq!(resolve(["en_US.UTF-8"]), vec!["en"]);
+        assert_eq!(resolve(["C"]), vec!["en"]);
+        assert_eq!(resolve(["xx_YY", "nl_NL@euro"]), vec!["nl", "en"]);
+        assert_eq!(resolve([]), vec!["en"]);
+    }
+
+    #[test]
+    fn test_emoji_names_follow_the_locale_chain() {
+        let coffee = EMOJIS.iter().find(|e| e.ch == "☕").unwrap();
+        assert_eq!(coffee.localized.len(), LOCALES.len());
+        assert!(coffee.name(&["nl"]).contains("warme drank"));
+        assert!(coffee.name(&["xx"]).contains("hot beverage"));
+        assert!(coffee.localized_in("nl").unwrap().keywords.contains(&"koffie"));
+        assert!(coffee.keywords(&["nl"]).contains(&"koffie"));
+        assert!(coffee.keywords(&["xx"]).contains(&"coffee"));
+        assert_eq!(coffee.localized_for(&["en"]).count(), 1);
+    }
+}
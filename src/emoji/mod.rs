@@ -1,2 +1,16 @@
+pub mod collections;
+pub mod coverage;
 pub mod emoji_data;
+pub mod favorites;
+pub mod frecency;
+pub mod fuzzy;
+pub mod index;
+pub mod kaomoji;
+pub mod kaomoji_data;
+pub mod locale;
 pub mod search;
+pub mod search_index;
+pub mod skin_tone;
+pub mod unicode;
+pub mod unicode_data;
+pub mod usage;
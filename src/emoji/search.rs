@@ -1,19 +1,290 @@
-use crate::emoji::emoji_data::{Emoji, EMOJIS};
+use crate::emoji::emoji_data::{Emoji, EMOJIS, LOCALES};
+use crate::emoji::frecency;
+use crate::emoji::fuzzy::{self, Matcher};
+use crate::emoji::index;
+use crate::emoji::locale;
+use crate::emoji::usage::UsageHistory;
 
+/// Maximum number of results returned by [`search`].
+pub const MAX_RESULTS: usize = 40;
+
+/// Search `EMOJIS` in the user's locales (see [`locale::current`]).
 pub fn search(query: &str) -> Vec<&'static Emoji> {
+    search_with_usage(query, locale::current(), &UsageHistory::default(), 0)
+}
+
+/// Like [`search`], but matches names and keywords in `locales` and ranks the matches by text
+/// match and frecency from `usage`.
+pub fn search_with_usage(query: &str, locales: &[&str], usage: &UsageHistory, now: u64) -> Vec<&'static Emoji> {
     if query.is_empty() {
-        return EMOJIS.iter().take(40).collect();
+        return EMOJIS.iter().take(MAX_RESULTS).collect();
     }
 
-    let q = query.to_lowercase();
+    rank_indexed(query, locales, usage, now).into_iter().take(MAX_RESULTS).collect()
+}
+
+/// Return all emoji in `emojis` whose names or keywords in `locales` match `query`, best first.
+///
+/// The order combines how well the text matches (see [`match_score`]) with how often and
+/// how recently each emoji was picked according to `usage`. Ties keep table order.
+///
+/// This scans every emoji; for the built-in table [`rank_indexed`] gives the same result faster.
+pub fn rank<'a>(
+    emojis: &'a [Emoji],
+    query: &str,
+    locales: &[&str],
+    usage: &UsageHistory,
+    now: u64,
+) -> Vec<&'a Emoji> {
+    sort_by_score(score_all(emojis, &mut Query::new(query), locales, usage, now))
+}
+
+/// Like [`rank`] over `EMOJIS`, but looks the query up in the precomputed search index.
+pub fn rank_indexed(query: &str, locales: &[&str], usage: &UsageHistory, now: u64) -> Vec<&'static Emoji> {
+    sort_by_score(score_indexed(&mut Query::new(query), locales, usage, now))
+}
+
+/// Like [`rank_indexed`], with the emoji in `extra`, which are not in the index (such as user
+/// snippets), ranked among those of `EMOJIS`. Ties put `EMOJIS` first.
+pub fn rank_indexed_with<'a>(
+    extra: &'a [Emoji],
+    query: &str,
+    locales: &[&str],
+    usage: &UsageHistory,
+    now: u64,
+) -> Vec<&'a Emoji> {
+    let mut query = Query::new(query);
+    let mut scored: Vec<(f64, &Emoji)> = score_indexed(&mut query, locales, usage, now);
+    scored.extend(score_all(extra, &mut query, locales, usage, now));
+    sort_by_score(scored)
+}
 
-    EMOJIS
+/// The emoji in `emojis` that match `query`, with their score including frecency.
+fn score_all<'a>(
+    emojis: &'a [Emoji],
+    query: &mut Query,
+    locales: &[&str],
+    usage: &UsageHistory,
+    now: u64,
+) -> Vec<(f64, &'a Emoji)> {
+    emojis
         .iter()
-        .filter(|e| {
-            e.name_en.to_lowercase().contains(&q)
-                || e.keywords_en.iter().any(|k| k.to_lowercase().contains(&q))
+        .filter_map(|e| {
+            let m = score_emoji(e, locales, query)?;
+            Some((frecency::combine(m, frecency::normalized(usage.get(e.ch), now)), e))
+        })
+        .collect()
+}
+
+/// Like [`score_all`] over `EMOJIS`, using the search index.
+fn score_indexed(query: &mut Query, locales: &[&str], usage: &UsageHistory, now: u64) -> Vec<(f64, &'static Emoji)> {
+    // The index holds texts per compiled-in locale, in `LOCALES` order
+    let selected: Vec<bool> = LOCALES.iter().map(|l| locales.contains(l)).collect();
+    let mut names: Vec<&str> = Vec::new();
+    let mut keywords: Vec<&str> = Vec::new();
+    index::candidates(query.phrase.query())
+        .into_iter()
+        .filter_map(|i| {
+            let e = &EMOJIS[i];
+            names.clear();
+            keywords.clear();
+            for (texts, _) in index::texts(i).localized.iter().zip(&selected).filter(|(_, &s)| s) {
+                names.push(texts.name);
+                keywords.extend_from_slice(texts.keywords);
+            }
+            let m = score_texts(e.ch, &names, &keywords, query)?;
+            Some((frecency::combine(m, frecency::normalized(usage.get(e.ch), now)), e))
         })
-        .take(40)
         .collect()
 }
 
+fn sort_by_score(mut scored: Vec<(f64, &Emoji)>) -> Vec<&Emoji> {
+    // Stable sort, so ties keep table order
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().map(|(_, e)| e).collect()
+}
+
+/// Bonus for multi-word queries that also match as one phrase ("red heart" in "red heart").
+pub const PHRASE_BONUS: f64 = 0.1;
+
+/// A lowercased query, prepared as one phrase and as its separate words.
+struct Query {
+    phrase: Matcher,
+    words: Vec<Matcher>,
+}
+
+impl Query {
+    fn new(query: &str) -> Self {
+        let q = query.to_lowercase();
+        let words = q.split_whitespace().map(Matcher::new).collect();
+        Self {
+            phrase: Matcher::new(&q),
+            words,
+        }
+    }
+}
+
+/// Score how well `emoji` matches the lowercase query `q` in `locales`, from 0.0 to 1.0.
+///
+/// Names score higher than keywords; see [`crate::emoji::fuzzy::score`] for how a single text
+/// is scored. A query of several words matches only if every word matches some name or
+/// keyword (in any of `locales`); its score is the average over the words, plus [`PHRASE_BONUS`]
+/// if the whole query also appears as a phrase. Returns `None` if the emoji does not match.
+pub fn match_score(emoji: &Emoji, q: &str, locales: &[&str]) -> Option<f64> {
+    score_emoji(emoji, locales, &mut Query::new(q))
+}
+
+fn score_emoji(emoji: &Emoji, locales: &[&str], query: &mut Query) -> Option<f64> {
+    let names: Vec<String> = emoji.localized_for(locales).map(|l| l.name.to_lowercase()).collect();
+    let mut keywords: Vec<String> = emoji
+        .localized_for(locales)
+        .flat_map(|l| l.keywords)
+        .map(|k| k.to_lowercase())
+        .collect();
+    // The subgroup counts as English keywords, as in the search index
+    if emoji.localized_for(locales).any(|l| l.locale == "en") {
+        keywords.extend(index::subgroup_terms(emoji.subgroup).map(str::to_string));
+    }
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let keywords: Vec<&str> = keywords.iter().map(String::as_str).collect();
+    score_texts(emoji.ch, &names, &keywords, query)
+}
+
+/// [`match_score`] on already lowercased names and keywords.
+fn score_texts(ch: &str, names: &[&str], keywords: &[&str], query: &mut Query) -> Option<f64> {
+    let q = query.phrase.query();
+    if ch == q {
+        return Some(1.0);
+    }
+    let in_ch = ch.contains(q).then_some(0.3);
+    let phrase = best_text_score(names, keywords, &mut query.phrase);
+    if query.words.len() < 2 {
+        return phrase.or(in_ch);
+    }
+    let mut total = 0.0;
+    for word in &mut query.words {
+        match best_text_score(names, keywords, word) {
+            Some(s) => total += s,
+            None => return in_ch,
+        }
+    }
+    let words = total / query.words.len() as f64;
+    match phrase {
+        Some(p) if p >= fuzzy::SUBSTRING_SCORE => Some((p.max(words) + PHRASE_BONUS).min(1.0)),
+        _ => Some(words),
+    }
+}
+
+/// Best score of `matcher` over the names and (weighted down) keywords of an emoji.
+fn best_text_score(names: &[&str], keywords: &[&str], matcher: &mut Matcher) -> Option<f64> {
+    let mut best: Option<f64> = None;
+    for n in names {
+        if let Some(s) = matcher.score(n) {
+            best = Some(best.map_or(s, |b| b.max(s)));
+        }
+    }
+    for k in keywords {
+        if let Some(s) = matcher.score(k) {
+            best = Some(best.map_or(s * 0.8, |b| b.max(s * 0.8)));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EN: &[&str] = &["en"];
+
+    fn position(results: &[&Emoji], ch: &str) -> usize {
+        results.iter().position(|e| e.ch == ch).expect("emoji not in results")
+    }
+
+    #[test]
+    fn test_match_score_prefers_names_and_exact_matches() {
+        let heart = EMOJIS.iter().find(|e| e.ch == "❤️").unwrap();
+        assert!(match_score(heart, "heart", EN).is_some());
+        assert!(match_score(heart, "no such thing", EN).is_none());
+        assert_eq!(match_score(heart, "❤️", EN), Some(1.0));
+        let unicorn = EMOJIS.iter().find(|e| e.ch == "🦄").unwrap();
+        assert!(match_score(unicorn, "unicorn", EN).unwrap() > match_score(unicorn, "unicron", EN).unwrap());
+    }
+
+    #[test]
+    fn test_frecency_lifts_frequently_used_emoji() {
+        let now = 100_000_000;
+        let plain = rank(EMOJIS, "heart", EN, &UsageHistory::default(), now);
+        let mut usage = UsageHistory::default();
+        for _ in 0..5 {
+            usage.record("❤️", now);
+        }
+        usage.record("🫀", now - 60 * 24 * 60 * 60);
+        let ranked = rank(EMOJIS, "heart", EN, &usage, now);
+        assert_eq!(search_with_usage("heart", EN, &usage, now)[0].ch, "❤️");
+        assert!(position(&ranked, "❤️") < position(&ranked, "🫀"));
+        assert!(position(&ranked, "❤️") <= position(&plain, "❤️"));
+        assert_eq!(plain.len(), ranked.len());
+    }
+
+    #[test]
+    fn test_multi_word_queries_match_every_word_in_any_order() {
+        let search = |query| search_with_usage(query, EN, &UsageHistory::default(), 0);
+        assert_eq!(search("heart red")[0].ch, "❤️");
+        let results = search("cat smiling");
+        assert!(!results.is_empty());
+        for e in &results {
+            let mut texts = e.localized_for(EN).map(|l| format!("{} {}", l.name, l.keywords.join(" ")));
+            assert!(texts.any(|t| t.to_lowercase().contains("cat")), "{} has no cat", e.ch);
+        }
+        assert!(search("unicorn pizza").is_empty());
+    }
+
+    #[test]
+    fn test_phrase_match_gets_a_bonus() {
+        let heart = EMOJIS.iter().find(|e| e.ch == "❤️").unwrap();
+        let phrase = match_score(heart, "red heart", EN).unwrap();
+        let words = match_score(heart, "heart red", EN).unwrap();
+        assert!(phrase > words);
+        assert!(phrase - words >= PHRASE_BONUS - 1e-9);
+    }
+
+    #[test]
+    fn test_search_only_matches_the_given_locales() {
+        let coffee = EMOJIS.iter().find(|e| e.ch == "☕").unwrap();
+        assert!(match_score(coffee, "koffie", &["nl"]).is_some());
+        assert!(match_score(coffee, "koffie", EN).is_none());
+        assert!(match_score(coffee, "coffee", &["nl"]).is_none());
+        assert_eq!(search_with_usage("koffie", &["nl", "en"], &UsageHistory::default(), 0)[0].ch, "☕");
+    }
+
+    #[test]
+    fn test_indexed_ranking_matches_full_scan() {
+        let usage = UsageHistory::default();
+        let queries = [
+            "heart", "eart", "red heart", "thmbup", "smiel", "koffie", "🦄", "d hea", "firefigther", "x",
+            "heart red", "cat smil", "face  tears", "eed", "t-rex",
+        ];
+        for locales in [LOCALES, EN, &["nl"]] {
+            for query in queries {
+                let scan: Vec<_> = rank(EMOJIS, query, locales, &usage, 0).iter().map(|e| e.ch).collect();
+                let indexed: Vec<_> = rank_indexed(query, locales, &usage, 0).iter().map(|e| e.ch).collect();
+                assert_eq!(scan, indexed, "query {:?} in {:?}", query, locales);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extra_emoji_are_ranked_among_the_indexed_ones() {
+        let snippets = crate::emoji::collections::parse(
+            "[[snippet]]\ntext = \"<3\"\nname = \"heart\"\n\n[[snippet]]\ntext = \"LGTM\"\nname = \"looks good\"\n",
+        )
+        .unwrap();
+        let ranked = rank_indexed_with(&snippets, "heart", EN, &UsageHistory::default(), 0);
+        assert_eq!(ranked.len(), rank_indexed("heart", EN, &UsageHistory::default(), 0).len() + 1);
+        // An exact name match, tied with the emoji named "heart" but after them
+        let snippet = position(&ranked, "<3");
+        assert!(snippet < ranked.len() / 2, "{} of {}", snippet, ranked.len());
+        assert!(rank_indexed_with(&snippets, "looks good", EN, &UsageHistory::default(), 0).iter().any(|e| e.ch == "LGTM"));
+    }
+}
@@ -0,0 +1,4 @@
+//! The precomputed search index, generated by build.rs into `OUT_DIR` from the same data as
+//! `emoji_data.rs` (see `index` for the lookups).
+
+include!(concat!(env!("OUT_DIR"), "/search_index.rs"));
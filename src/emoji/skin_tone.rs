@@ -0,0 +1,169 @@
+//! Skin tones: the Fitzpatrick modifiers and the user's preference for them.
+//!
+//! `EMOJIS` only lists the default (yellow) form of each emoji; emoji that support skin tones
+//! carry their five toned variants in `skin_tone_variants`, in the order of [`SkinTone::TONED`].
+
+use crate::emoji::emoji_data::Emoji;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A skin tone: the default (yellow) form or one of the five Fitzpatrick tones.
+///
+/// In `settings.toml` the tones are written in kebab-case: `default`, `light`, `medium-light`,
+/// `medium`, `medium-dark` and `dark`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkinTone {
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+impl SkinTone {
+    /// Every tone, default first, in the order a selector shows them.
+    pub const ALL: [SkinTone; 6] = [
+        SkinTone::Default,
+        SkinTone::Light,
+        SkinTone::MediumLight,
+        SkinTone::Medium,
+        SkinTone::MediumDark,
+        SkinTone::Dark,
+    ];
+
+    /// The five real tones, in the order of `Emoji::skin_tone_variants`.
+    pub const TONED: [SkinTone; 5] = [
+        SkinTone::Light,
+        SkinTone::MediumLight,
+        SkinTone::Medium,
+        SkinTone::MediumDark,
+        SkinTone::Dark,
+    ];
+
+    /// Index into `Emoji::skin_tone_variants`, or `None` for the default tone.
+    pub fn variant_index(self) -> Option<usize> {
+        Self::TONED.iter().position(|&t| t == self)
+    }
+
+    /// The Fitzpatrick modifier character for this tone (empty for the default tone).
+    pub fn modifier(self) -> &'static str {
+        match self {
+            SkinTone::Default => "",
+            SkinTone::Light => "\u{1F3FB}",
+            SkinTone::MediumLight => "\u{1F3FC}",
+            SkinTone::Medium => "\u{1F3FD}",
+            SkinTone::MediumDark => "\u{1F3FE}",
+            SkinTone::Dark => "\u{1F3FF}",
+        }
+    }
+
+    /// Human readable name, for selectors and tooltips.
+    pub fn label(self) -> &'static str {
+        match self {
+            SkinTone::Default => "Default",
+            SkinTone::Light => "Light",
+            SkinTone::MediumLight => "Medium-light",
+            SkinTone::Medium => "Medium",
+            SkinTone::MediumDark => "Medium-dark",
+            SkinTone::Dark => "Dark",
+        }
+    }
+}
+
+impl Emoji {
+    /// Whether this emoji comes in different skin tones.
+    pub fn has_skin_tones(&self) -> bool {
+        self.skin_tone_variants.is_some()
+    }
+
+    /// This emoji in `tone`, or the emoji itself if it has no skin tones.
+    pub fn with_skin_tone(&self, tone: SkinTone) -> &'static str {
+        match (self.skin_tone_variants, tone.variant_index()) {
+            (Some(variants), Some(i)) => variants[i],
+            _ => self.ch,
+        }
+    }
+
+    /// The emoji and its toned variants, one per entry of [`SkinTone::ALL`] it supports.
+    pub fn skin_tone_choices(&self) -> Vec<(SkinTone, &'static str)> {
+        if !self.has_skin_tones() {
+            return vec![(SkinTone::Default, self.ch)];
+        }
+        SkinTone::ALL.iter().map(|&tone| (tone, self.with_skin_tone(tone))).collect()
+    }
+}
+
+/// The preferred skin tone, with overrides for single emoji.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkinTonePreference {
+    /// Tone applied to every emoji that supports skin tones.
+    pub default: SkinTone,
+    /// Tones picked for single emoji (keyed by the default form), overriding `default`.
+    pub per_emoji: BTreeMap<String, SkinTone>,
+}
+
+impl SkinTonePreference {
+    /// The tone to use for the emoji whose default form is `ch`.
+    pub fn tone_for(&self, ch: &str) -> SkinTone {
+        self.per_emoji.get(ch).copied().unwrap_or(self.default)
+    }
+
+    /// Remember `tone` for the emoji `ch`. Picking the global tone removes the override.
+    pub fn set_for(&mut self, ch: &str, tone: SkinTone) {
+        if tone == self.default {
+            self.per_emoji.remove(ch);
+        } else {
+            self.per_emoji.insert(ch.to_string(), tone);
+        }
+    }
+
+    /// The text to show and copy for `emoji` under this preference.
+    pub fn apply(&self, emoji: &Emoji) -> &'static str {
+        emoji.with_skin_tone(self.tone_for(emoji.ch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::emoji_data::EMOJIS;
+
+    fn emoji(ch: &str) -> &'static Emoji {
+        EMOJIS.iter().find(|e| e.ch == ch).unwrap()
+    }
+
+    #[test]
+    fn test_with_skin_tone_picks_the_variant() {
+        let thumbs_up = emoji("👍");
+        assert!(thumbs_up.has_skin_tones());
+        assert_eq!(thumbs_up.with_skin_tone(SkinTone::Default), "👍");
+        assert_eq!(thumbs_up.with_skin_tone(SkinTone::Medium), "👍🏽");
+        assert_eq!(thumbs_up.with_skin_tone(SkinTone::Dark), "👍🏿");
+        assert_eq!(thumbs_up.skin_tone_choices().len(), SkinTone::ALL.len());
+        let pizza = emoji("🍕");
+        assert_eq!(pizza.with_skin_tone(SkinTone::Dark), "🍕");
+        assert_eq!(pizza.skin_tone_choices(), vec![(SkinTone::Default, "🍕")]);
+    }
+
+    #[test]
+    fn test_variants_use_the_matching_modifier() {
+        let wave = emoji("👋");
+        for tone in SkinTone::TONED {
+            assert!(wave.with_skin_tone(tone).contains(tone.modifier()), "{:?}", tone);
+        }
+    }
+
+    #[test]
+    fn test_preference_applies_overrides_before_the_default() {
+        let mut preference = SkinTonePreference { default: SkinTone::Light, ..Default::default() };
+        preference.set_for("👍", SkinTone::Dark);
+        assert_eq!(preference.apply(emoji("👍")), "👍🏿");
+        assert_eq!(preference.apply(emoji("👋")), "👋🏻");
+        preference.set_for("👍", SkinTone::Light);
+        assert!(preference.per_emoji.is_empty());
+    }
+}
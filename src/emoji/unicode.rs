@@ -0,0 +1,166 @@
+//! The Unicode character database, for picking arrows, math symbols, box drawing, dashes and
+//! any other character by name, block or codepoint.
+//!
+//! [`CHARACTERS`] is generated by build.rs from the vendored `data/UnicodeData.txt` and
+//! `data/Blocks.txt`. It holds every character with a name of its own; the ranges named by
+//! codepoint, such as CJK ideographs and Hangul syllables, are left out.
+
+pub use crate::emoji::unicode_data::{BLOCKS, CHARACTERS};
+
+use crate::emoji::emoji_data::Emoji;
+
+/// Most characters a search returns; a single word can match thousands of names.
+pub const MAX_RESULTS: usize = 500;
+
+/// The codepoint of a character from [`CHARACTERS`].
+pub fn codepoint(character: &Emoji) -> u32 {
+    character.ch.chars().next().map_or(0, u32::from)
+}
+
+/// The codepoint written in `query`: `U+2192`, `0x2192`, or bare hex with at least four digits,
+/// one of them a decimal digit (so words like "face" are not read as a codepoint).
+pub fn parse_codepoint(query: &str) -> Option<u32> {
+    let query = query.trim();
+    let prefixed = ["U+", "u+", "0x", "0X"].iter().find_map(|prefix| query.strip_prefix(prefix));
+    let hex = match prefixed {
+        Some(hex) => hex,
+        None if query.len() >= 4 && query.chars().any(|c| c.is_ascii_digit()) => query,
+        None => return None,
+    };
+    if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// The named character at `codepoint`, if any.
+pub fn character(codepoint: u32) -> Option<&'static Emoji> {
+    CHARACTERS
+        .binary_search_by_key(&codepoint, self::codepoint)
+        .ok()
+        .map(|index| &CHARACTERS[index])
+}
+
+/// The blocks that have named characters, in codepoint order.
+pub fn blocks() -> Vec<&'static str> {
+    let mut blocks: Vec<&'static str> = CHARACTERS.iter().map(|c| c.category).collect();
+    blocks.dedup();
+    blocks
+}
+
+/// The named characters of `block`, in codepoint order.
+pub fn in_block(block: &str) -> &'static [Emoji] {
+    let start = CHARACTERS.iter().position(|c| c.category == block).unwrap_or(CHARACTERS.len());
+    let len = CHARACTERS[start..].iter().take_while(|c| c.category == block).count();
+    &CHARACTERS[start..start + len]
+}
+
+/// Whether every word of `query` starts a word of `text` (words split at spaces and hyphens).
+fn words_match(query: &[&str], text: &str) -> bool {
+    query.iter().all(|q| text.split([' ', '-']).any(|word| word.starts_with(q)))
+}
+
+/// How well `name` matches `query`, lower is better, or `None` if it does not.
+fn name_score(name: &str, query: &str, words: &[&str]) -> Option<u8> {
+    if name == query {
+        Some(0)
+    } else if name.starts_with(query) {
+        Some(1)
+    } else if words_match(words, name) {
+        Some(2)
+    } else if words.iter().all(|w| name.contains(w)) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// The characters matching `query`, best first, at most [`MAX_RESULTS`].
+///
+/// A codepoint (see [`parse_codepoint`]) comes first. Then come names: exact, then starting
+/// with the query, then having words starting with each query word, then containing them, and
+/// shorter names first within each. Last come the characters of blocks whose name matches.
+pub fn search(query: &str) -> Vec<&'static Emoji> {
+    let query = query.trim().to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let blocks: Vec<&str> = BLOCKS
+        .iter()
+        .map(|&(_, _, name)| name)
+        .filter(|name| words_match(&words, &name.to_lowercase()))
+        .collect();
+
+    let by_codepoint = parse_codepoint(&query).and_then(character);
+    let mut scored: Vec<(u8, usize, &'static Emoji)> = CHARACTERS
+        .iter()
+        .filter_map(|c| {
+            let name = c.localized[0].name;
+            let score = name_score(name, &query, &words).or_else(|| blocks.contains(&c.category).then_some(4))?;
+            Some((score, name.len(), c))
+        })
+        .collect();
+    // Stable: equally good matches stay in codepoint order
+    scored.sort_by_key(|&(score, len, _)| (score, len));
+
+    let mut results: Vec<&'static Emoji> = by_codepoint.into_iter().collect();
+    let by_name = scored.into_iter().map(|(_, _, c)| c);
+    results.extend(by_name.filter(|&c| by_codepoint.map(|b| b.ch) != Some(c.ch)));
+    results.truncate(MAX_RESULTS);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codepoint() {
+        assert_eq!(parse_codepoint("U+2192"), Some(0x2192));
+        assert_eq!(parse_codepoint("u+1f600"), Some(0x1F600));
+        assert_eq!(parse_codepoint("0x2014"), Some(0x2014));
+        assert_eq!(parse_codepoint("2192"), Some(0x2192));
+        assert_eq!(parse_codepoint("face"), None);
+        assert_eq!(parse_codepoint("12"), None);
+        assert_eq!(parse_codepoint("U+"), None);
+        assert_eq!(parse_codepoint("U+12345678"), None);
+        assert_eq!(parse_codepoint("arrow"), None);
+    }
+
+    #[test]
+    fn test_characters_are_sorted_and_named() {
+        assert!(CHARACTERS.windows(2).all(|w| codepoint(&w[0]) < codepoint(&w[1])));
+        assert!(CHARACTERS.iter().all(|c| c.ch.chars().count() == 1 && !c.localized[0].name.is_empty()));
+        assert_eq!(character(0x2192).unwrap().ch, "→");
+        assert_eq!(character(0x2014).unwrap().localized[0].name, "em dash");
+        assert!(character(0x4E00).is_none(), "CJK ideographs are named by codepoint");
+        assert!(character(0x0007).is_none(), "controls have no name");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = blocks();
+        assert_eq!(blocks[0], "Basic Latin");
+        assert!(blocks.contains(&"Arrows") && blocks.contains(&"Box Drawing"));
+        let arrows = in_block("Arrows");
+        assert_eq!(arrows[0].ch, "←");
+        assert!(arrows.iter().all(|c| c.category == "Arrows"));
+        assert!(in_block("No such block").is_empty());
+    }
+
+    #[test]
+    fn test_search_by_name_codepoint_and_block() {
+        assert_eq!(search("rightwards arrow")[0].ch, "→");
+        assert_eq!(search("RIGHTWARDS ARROW")[0].ch, "→");
+        assert_eq!(search("U+2192")[0].ch, "→");
+        assert_eq!(search("em dash")[0].ch, "—");
+        assert_eq!(search("euro sign")[0].ch, "€");
+        let box_drawing = search("box drawing");
+        assert!(box_drawing.iter().any(|c| c.ch == "─"));
+        assert!(search("drawing light horiz").iter().any(|c| c.ch == "─"));
+        assert_eq!(search("letter").len(), MAX_RESULTS);
+        assert!(search("").is_empty());
+        assert!(search("xyzzy").is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+//! The Unicode characters, generated by build.rs into `OUT_DIR` from the vendored
+//! `data/UnicodeData.txt` and `data/Blocks.txt` (see `unicode` for the lookups).
+
+include!(concat!(env!("OUT_DIR"), "/unicode_data.rs"));
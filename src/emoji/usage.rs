@@ -0,0 +1,231 @@
+//! Persistent usage history of picked emoji.
+//!
+//! Every pick is recorded with a use count and the time it was last used. The history is
+//! kept most-recent-first and capped at a configurable number of entries; when the cap is
+//! exceeded, the least recently used entries are dropped.
+//!
+//! The history is stored as a small tab-separated text file (`emoji<TAB>count<TAB>last_used`)
+//! so it can be inspected and edited by hand.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default maximum number of emoji remembered in the history.
+pub const DEFAULT_CAPACITY: usize = 40;
+
+/// File name of the history inside the state directory.
+pub const HISTORY_FILE: &str = "history.tsv";
+
+const HEADER: &str = "# emoji-picker usage history v1";
+
+/// Usage statistics for a single emoji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageEntry {
+    /// The exact text that was picked.
+    pub ch: String,
+    /// How many times the emoji has been picked.
+    pub count: u32,
+    /// Seconds since the UNIX epoch of the last pick.
+    pub last_used: u64,
+}
+
+/// Most-recent-first history of picked emoji, capped at `capacity` entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageHistory {
+    entries: Vec<UsageEntry>,
+    capacity: usize,
+}
+
+impl Default for UsageHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl UsageHistory {
+    /// Create an empty history that keeps at most `capacity` emoji.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Record a pick of `ch` at time `now` (seconds since the UNIX epoch).
+    pub fn record(&mut self, ch: &str, now: u64) {
+        let entry = match self.entries.iter().position(|e| e.ch == ch) {
+            Some(pos) => {
+                let mut entry = self.entries.remove(pos);
+                entry.count = entry.count.saturating_add(1);
+                entry.last_used = entry.last_used.max(now);
+                entry
+            }
+            None => UsageEntry {
+                ch: ch.to_string(),
+                count: 1,
+                last_used: now,
+            },
+        };
+        self.entries.insert(0, entry);
+        self.entries.truncate(self.capacity);
+    }
+
+    /// All entries, most recently used first.
+    pub fn entries(&self) -> &[UsageEntry] {
+        &self.entries
+    }
+
+    /// Look up the statistics for `ch`, if it has been picked before.
+    pub fn get(&self, ch: &str) -> Option<&UsageEntry> {
+        self.entries.iter().find(|e| e.ch == ch)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the maximum size, dropping the least recently used entries if needed.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.truncate(capacity);
+    }
+
+    /// Parse a history from its on-disk text form. Malformed lines are skipped.
+    pub fn parse(text: &str, capacity: usize) -> Self {
+        let mut entries: Vec<UsageEntry> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let ch = fields.next()?.to_string();
+                let count = fields.next()?.trim().parse().ok()?;
+                let last_used = fields.next()?.trim().parse().ok()?;
+                if ch.is_empty() {
+                    return None;
+                }
+                Some(UsageEntry { ch, count, last_used })
+            })
+            .collect();
+        // The file is written most-recent-first, but don't rely on hand-edited files.
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+        let mut seen = HashSet::new();
+        entries.retain(|e| seen.insert(e.ch.clone()));
+        entries.truncate(capacity);
+        Self { entries, capacity }
+    }
+
+    /// Serialize the history to its on-disk text form.
+    pub fn to_text(&self) -> String {
+        let mut out = String::from(HEADER);
+        out.push('\n');
+        for e in &self.entries {
+            out.push_str(&format!("{}\t{}\t{}\n", e.ch, e.count, e.last_used));
+        }
+        out
+    }
+
+    /// Load the history from `path`. A missing or unreadable file yields an empty history.
+    pub fn load(path: &Path, capacity: usize) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text, capacity),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Could not read usage history {}: {}", path.display(), err);
+                }
+                Self::new(capacity)
+            }
+        }
+    }
+
+    /// Write the history to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never leaves a truncated history.
+        let tmp = path.with_extension("tsv.tmp");
+        fs::write(&tmp, self.to_text())?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// Default location of the history file: `$XDG_STATE_HOME/emoji-picker/history.tsv`.
+pub fn default_path() -> PathBuf {
+    crate::paths::state_dir().join(HISTORY_FILE)
+}
+
+/// Current time in seconds since the UNIX epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_moves_emoji_to_front_and_counts() {
+        let mut history = UsageHistory::new(10);
+        history.record("😀", 100);
+        history.record("👍", 200);
+        history.record("😀", 300);
+        let chars: Vec<_> = history.entries().iter().map(|e| e.ch.as_str()).collect();
+        assert_eq!(chars, vec!["😀", "👍"]);
+        assert_eq!(history.get("😀").unwrap().count, 2);
+        assert_eq!(history.get("😀").unwrap().last_used, 300);
+        assert_eq!(history.get("👍").unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_capacity_drops_least_recently_used() {
+        let mut history = UsageHistory::new(2);
+        history.record("😀", 1);
+        history.record("👍", 2);
+        history.record("🎉", 3);
+        assert_eq!(history.len(), 2);
+        assert!(history.get("😀").is_none());
+        history.set_capacity(1);
+        let chars: Vec<_> = history.entries().iter().map(|e| e.ch.as_str()).collect();
+        assert_eq!(chars, vec!["🎉"]);
+    }
+
+    #[test]
+    fn test_text_round_trip_and_malformed_lines() {
+        let mut history = UsageHistory::new(10);
+        history.record("❤️", 10);
+        history.record("👍🏽", 20);
+        let parsed = UsageHistory::parse(&history.to_text(), 10);
+        assert_eq!(parsed, history);
+
+        let text = "# comment\n😀\t3\t50\nbroken line\n🎉\tx\t1\n👍\t1\t60\n";
+        let parsed = UsageHistory::parse(text, 10);
+        let chars: Vec<_> = parsed.entries().iter().map(|e| e.ch.as_str()).collect();
+        assert_eq!(chars, vec!["👍", "😀"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-usage-{}", std::process::id()));
+        let path = dir.join(HISTORY_FILE);
+        let mut history = UsageHistory::new(5);
+        history.record("🎉", 42);
+        history.save(&path).unwrap();
+        assert_eq!(UsageHistory::load(&path, 5), history);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(UsageHistory::load(&path, 5).is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! Line-based output for external launchers such as dmenu, rofi, wofi and fuzzel.
+//!
+//! `emoji-picker --dmenu` writes one line per emoji, rendered from a template, and
+//! `emoji-picker --pick-from-stdin` reads the line the launcher returns and delivers its emoji
+//! like the picker would:
+//!
+//! ```sh
+//! emoji-picker --dmenu | rofi -dmenu -i | emoji-picker --pick-from-stdin
+//! ```
+//!
+//! Templates may contain `{emoji}`, `{name}`, `{keywords}`, `{category}` and `{codepoints}`.
+//! The emoji is found back by looking for a known emoji among the words of the chosen line,
+//! so any template works as long as it includes `{emoji}` as a separate word.
+
+use crate::cli::code_points;
+use crate::emoji::emoji_data::Emoji;
+use crate::emoji::skin_tone::SkinTonePreference;
+use crate::ui::app_controller::EmojiPickerController;
+
+/// Template used when neither `--template` nor the `line_template` setting is given.
+pub const DEFAULT_LINE_TEMPLATE: &str = "{emoji} {name} — {keywords}";
+
+/// Render the launcher line for `emoji`, shown as `text` (e.g. in the preferred skin tone).
+pub fn render_line(template: &str, emoji: &Emoji, text: &str, locales: &[&str]) -> String {
+    template
+        .replace("{emoji}", text)
+        .replace("{name}", emoji.name(locales))
+        .replace("{keywords}", &emoji.keywords(locales).join(", "))
+        .replace("{category}", emoji.category)
+        .replace("{codepoints}", &code_points(text))
+}
+
+/// Launcher lines for the emoji `controller` shows (see [`EmojiPickerController::is_shown`]),
+/// in table order so launchers see a stable list.
+pub fn lines(controller: &EmojiPickerController, template: &str, skin_tones: &SkinTonePreference) -> Vec<String> {
+    controller
+        .all_emojis
+        .iter()
+        .filter(|emoji| controller.is_shown(emoji))
+        .map(|emoji| render_line(template, emoji, skin_tones.apply(emoji), &controller.locales))
+        .collect()
+}
+
+/// The emoji text in a line chosen in the launcher: the first word that is a known emoji.
+///
+/// The word is returned as is, so a skin tone variant stays that variant.
+pub fn pick_from_line<'a>(controller: &EmojiPickerController, line: &'a str) -> Option<&'a str> {
+    line.split_whitespace().find(|word| controller.lookup(word).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::emoji_data::EMOJIS;
+    use crate::emoji::skin_tone::SkinTone;
+
+    fn controller() -> EmojiPickerController {
+        let mut controller = EmojiPickerController::new(EMOJIS.to_vec());
+        controller.locales = vec!["en"];
+        controller
+    }
+
+    #[test]
+    fn test_default_template_puts_the_emoji_first() {
+        let controller = controller();
+        let lines = lines(&controller, DEFAULT_LINE_TEMPLATE, &SkinTonePreference::default());
+        assert_eq!(lines.len(), controller.shown_emojis().len());
+        // Only the fully-qualified form of an emoji is listed, as in the picker
+        assert!(lines.iter().any(|l| l.starts_with("☺️ ")));
+        assert!(!lines.iter().any(|l| l.starts_with("☺ ")));
+        let tada = lines.iter().find(|l| l.starts_with("🎉 ")).unwrap();
+        assert!(tada.contains("party popper — "), "{}", tada);
+        assert!(tada.contains("celebration"), "{}", tada);
+    }
+
+    #[test]
+    fn test_custom_template_and_skin_tone() {
+        let controller = controller();
+        let thumbs_up = controller.lookup("👍").unwrap();
+        let line = render_line("{codepoints}\t{emoji}\t{category}", thumbs_up, "👍🏽", &controller.locales);
+        assert_eq!(line, "U+1F44D U+1F3FD\t👍🏽\tPeople & Body");
+        let skin_tones = SkinTonePreference { default: SkinTone::Dark, ..Default::default() };
+        assert!(lines(&controller, "{emoji}", &skin_tones).contains(&"👍🏿".to_string()));
+    }
+
+    #[test]
+    fn test_pick_from_line_finds_the_emoji_word() {
+        let controller = controller();
+        assert_eq!(pick_from_line(&controller, "🎉 party popper — celebration, party\n"), Some("🎉"));
+        assert_eq!(pick_from_line(&controller, "U+1F44D U+1F3FD\t👍🏽\tPeople & Body"), Some("👍🏽"));
+        assert_eq!(pick_from_line(&controller, "no emoji here"), None);
+        assert_eq!(pick_from_line(&controller, ""), None);
+    }
+}
@@ -1,3 +1,11 @@
 pub mod emoji;
 pub mod ui;
+pub mod cli;
 pub mod clipboard;
+pub mod dbus;
+pub mod launcher;
+pub mod output;
+pub mod paths;
+pub mod resources;
+pub mod settings;
+pub mod trace;
@@ -1,7 +1,24 @@
 mod app;
+mod cli;
+mod dbus;
+mod launcher;
 mod ui;
 mod emoji;
 mod clipboard;
+mod output;
+mod paths;
+mod resources;
+mod settings;
+mod trace;
 fn main() {
-    app::run();
-}
\ No newline at end of file
+    // Subcommands print and exit before GTK is initialized, so they need no display
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse(&args) {
+        Ok(Some(invocation)) => std::process::exit(cli::run(&invocation)),
+        Ok(None) => app::run(),
+        Err(message) => {
+            eprintln!("emoji-picker: {}\n\n{}", message, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Output backends: how a picked emoji reaches the user.
+//!
+//! In [`OutputMode::Copy`] the emoji goes to the clipboard (see `clipboard.rs`). In
+//! [`OutputMode::Insert`] it is typed into the application that had focus before the picker,
+//! using one of the [`Inserter`] backends available in the session:
+//!
+//! - Wayland: `wtype`, which types through the virtual-keyboard protocol
+//!   (`zwp_virtual_keyboard_v1`, supported by wlroots compositors and KDE, not by GNOME).
+//! - X11: `xdotool`, which types through the XTest extension.
+//!
+//! Both are external programs, optional at runtime: without them the picker only copies.
+//!
+//! The caller must hide the picker window before inserting, so that focus returns to the
+//! target application, and fall back to the clipboard when no backend works. Inside a
+//! Flatpak sandbox these tools are not available, so the clipboard is always used there.
+//!
+//! [`OutputMode::Copy`]: crate::settings::OutputMode::Copy
+//! [`OutputMode::Insert`]: crate::settings::OutputMode::Insert
+
+use gtk4::gio;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// Time between hiding the picker and typing, so the compositor can refocus the target window.
+pub const INSERT_DELAY: Duration = Duration::from_millis(150);
+
+/// The kind of graphical session the picker runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Session {
+    Wayland,
+    X11,
+    Unknown,
+}
+
+impl Session {
+    /// Detect the session from `WAYLAND_DISPLAY`, `DISPLAY` and `XDG_SESSION_TYPE`.
+    pub fn detect() -> Self {
+        let var = |name| env::var(name).ok().filter(|v| !v.is_empty());
+        Self::from_vars(var("WAYLAND_DISPLAY"), var("DISPLAY"), var("XDG_SESSION_TYPE"))
+    }
+
+    fn from_vars(wayland_display: Option<String>, display: Option<String>, session_type: Option<String>) -> Self {
+        if wayland_display.is_some() {
+            return Session::Wayland;
+        }
+        if display.is_some() {
+            return Session::X11;
+        }
+        match session_type.as_deref() {
+            Some("wayland") => Session::Wayland,
+            Some("x11") => Session::X11,
+            _ => Session::Unknown,
+        }
+    }
+}
+
+/// A backend that types text into the focused application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inserter {
+    /// `wtype`, using the Wayland virtual-keyboard protocol.
+    Wtype,
+    /// `xdotool`, using the X11 XTest extension.
+    Xdotool,
+}
+
+impl Inserter {
+    /// The external program this backend runs.
+    pub fn program(self) -> &'static str {
+        match self {
+            Inserter::Wtype => "wtype",
+            Inserter::Xdotool => "xdotool",
+        }
+    }
+
+    /// The backends that can work in `session`, best first, among the installed programs.
+    pub fn available(session: Session, has_program: impl Fn(&str) -> bool) -> Vec<Inserter> {
+        let candidates: &[Inserter] = match session {
+            Session::Wayland => &[Inserter::Wtype],
+            Session::X11 => &[Inserter::Xdotool],
+            Session::Unknown => &[],
+        };
+        candidates.iter().copied().filter(|i| has_program(i.program())).collect()
+    }
+
+    /// The backends available in the current session.
+    pub fn detect() -> Vec<Inserter> {
+        Self::available(Session::detect(), program_in_path)
+    }
+
+    /// The command that types `text`.
+    pub fn command(self, text: &str) -> Command {
+        let mut command = Command::new(self.program());
+        match self {
+            Inserter::Wtype => command.arg("--").arg(text),
+            Inserter::Xdotool => command.args(["type", "--clearmodifiers", "--"]).arg(text),
+        };
+        command
+    }
+
+    /// Type `text` into the focused application, running [`Inserter::command`] as a
+    /// [`gio::Subprocess`], so the main loop keeps running until it is done.
+    pub async fn insert_future(self, text: &str) -> io::Result<()> {
+        let command = self.command(text);
+        let argv: Vec<&OsStr> = std::iter::once(command.get_program()).chain(command.get_args()).collect();
+        let process = gio::Subprocess::newv(&argv, gio::SubprocessFlags::STDERR_PIPE)
+            .map_err(|err| io::Error::new(io::ErrorKind::NotFound, err.to_string()))?;
+        let (_, stderr) = process.communicate_future(None).await.map_err(|err| io::Error::other(err.to_string()))?;
+        if process.is_successful() {
+            Ok(())
+        } else {
+            let stderr = stderr.as_deref().unwrap_or_default();
+            Err(self.failure(format_args!("exit status: {}", process.exit_status()), stderr))
+        }
+    }
+
+    /// The error of a run that ended with `status`, after printing `stderr`.
+    fn failure(self, status: impl fmt::Display, stderr: &[u8]) -> io::Error {
+        let stderr = String::from_utf8_lossy(stderr);
+        io::Error::other(format!("{} failed ({}): {}", self.program(), status, stderr.trim()))
+    }
+}
+
+/// Type `text` with the first of `inserters` that succeeds.
+///
+/// Returns the error of the last backend tried, or `NotFound` if there are none; the caller
+/// should then fall back to the clipboard. The picker window awaits this on its main loop,
+/// the command line blocks on it.
+pub async fn insert_with(inserters: &[Inserter], text: &str) -> io::Result<Inserter> {
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no text insertion backend available");
+    for &inserter in inserters {
+        match inserter.insert_future(text).await {
+            Ok(()) => return Ok(inserter),
+            Err(err) => {
+                log::warn!("Could not insert with {}: {}", inserter.program(), err);
+                last_err = err;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// Whether an executable called `name` is on `PATH`.
+fn program_in_path(name: &str) -> bool {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).any(|dir| is_executable(&dir.join(name))))
+        .unwrap_or(false)
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn some(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    #[test]
+    fn test_session_detection() {
+        assert_eq!(Session::from_vars(some("wayland-0"), some(":0"), some("wayland")), Session::Wayland);
+        assert_eq!(Session::from_vars(None, some(":0"), some("x11")), Session::X11);
+        assert_eq!(Session::from_vars(None, some(":0"), None), Session::X11);
+        assert_eq!(Session::from_vars(None, None, some("wayland")), Session::Wayland);
+        assert_eq!(Session::from_vars(None, None, some("tty")), Session::Unknown);
+    }
+
+    #[test]
+    fn test_available_backends_depend_on_session_and_installed_programs() {
+        let all = |_: &str| true;
+        assert_eq!(Inserter::available(Session::Wayland, all), vec![Inserter::Wtype]);
+        assert_eq!(Inserter::available(Session::X11, all), vec![Inserter::Xdotool]);
+        assert!(Inserter::available(Session::Unknown, all).is_empty());
+        assert!(Inserter::available(Session::Wayland, |p| p == "xdotool").is_empty());
+    }
+
+    #[test]
+    fn test_commands_pass_the_text_as_a_single_argument() {
+        let args = |i: Inserter| -> Vec<String> {
+            i.command("-👍 x").get_args().map(|a| a.to_string_lossy().into_owned()).collect()
+        };
+        assert_eq!(args(Inserter::Wtype), vec!["--", "-👍 x"]);
+        assert_eq!(args(Inserter::Xdotool), vec!["type", "--clearmodifiers", "--", "-👍 x"]);
+    }
+
+    #[test]
+    fn test_insert_without_backends_asks_for_fallback() {
+        let err = gtk4::glib::MainContext::default().block_on(insert_with(&[], "👍")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
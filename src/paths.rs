@@ -0,0 +1,40 @@
+//! XDG base directory helpers for the per-user files of the emoji picker.
+
+use std::env;
+use std::path::PathBuf;
+
+/// Name of the application subdirectory inside each XDG base directory.
+pub const APP_DIR: &str = "emoji-picker";
+
+/// Resolve an XDG base directory from `var`, falling back to `$HOME/<fallback>`.
+///
+/// Relative values are ignored, as required by the XDG Base Directory specification.
+fn xdg_base_dir(var: &str, fallback: &str) -> PathBuf {
+    match env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir)
+            .join(fallback),
+    }
+}
+
+/// Directory for state that should persist between runs, such as usage history.
+pub fn state_dir() -> PathBuf {
+    xdg_base_dir("XDG_STATE_HOME", ".local/state").join(APP_DIR)
+}
+
+/// Directory for user configuration, such as settings.
+pub fn config_dir() -> PathBuf {
+    xdg_base_dir("XDG_CONFIG_HOME", ".config").join(APP_DIR)
+}
+
+/// Directory for data the user curates, such as favorites.
+pub fn data_dir() -> PathBuf {
+    xdg_base_dir("XDG_DATA_HOME", ".local/share").join(APP_DIR)
+}
+
+/// Directory for data that can be recomputed at any time, such as the font coverage.
+pub fn cache_dir() -> PathBuf {
+    xdg_base_dir("XDG_CACHE_HOME", ".cache").join(APP_DIR)
+}
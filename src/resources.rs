@@ -0,0 +1,13 @@
+//! Files compiled into the binary as a GResource bundle, listed in
+//! `data/emoji-picker.gresource.xml`.
+
+use gtk4::gio;
+use gtk4::glib;
+
+/// Resource path of the bundle, which GApplication derives from the application id.
+pub const BASE_PATH: &str = "/nl/dibitat/emoji_picker";
+
+/// Make the bundled files available under [`BASE_PATH`].
+pub fn register() -> Result<(), glib::Error> {
+    gio::resources_register_include!("emoji-picker.gresource")
+}
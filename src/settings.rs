@@ -0,0 +1,338 @@
+//! User settings, stored as TOML in `$XDG_CONFIG_HOME/emoji-picker/settings.toml`.
+//!
+//! Missing keys take their default value, so a partial (or missing) file is always valid.
+//! Invalid values are left out one by one, and out-of-range sizes are clamped when loading.
+
+use crate::emoji::locale;
+use crate::emoji::skin_tone::SkinTonePreference;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// File name of the settings inside the config directory.
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+/// Default number of grid columns.
+pub const DEFAULT_COLUMNS: i32 = 5;
+/// Default number of visible grid rows.
+pub const DEFAULT_ROWS: i32 = 6;
+/// Default emoji size, in points.
+pub const DEFAULT_EMOJI_SIZE: i32 = 32;
+
+/// Allowed number of grid columns.
+pub const COLUMNS_RANGE: RangeInclusive<i32> = 3..=16;
+/// Allowed number of visible grid rows.
+pub const ROWS_RANGE: RangeInclusive<i32> = 2..=16;
+/// Allowed emoji sizes, in points.
+pub const EMOJI_SIZE_RANGE: RangeInclusive<i32> = 16..=96;
+
+/// Color scheme of the picker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Follow the system light/dark preference.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// How the emoji categories are browsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BrowseLayout {
+    /// One page per category, switched with the category tabs.
+    #[default]
+    Paged,
+    /// All categories in one scrolling grid, each under its header; the tabs scroll to them.
+    Continuous,
+}
+
+/// What to do with a picked emoji.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputMode {
+    /// Put the emoji on the clipboard.
+    #[default]
+    Copy,
+    /// Type the emoji into the previously focused application, or copy it if that fails.
+    Insert,
+}
+
+/// Everything the user can configure, as read from and written to `settings.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Number of emoji per grid row.
+    pub columns: i32,
+    /// Number of grid rows visible without scrolling.
+    pub rows: i32,
+    /// Size of the emoji in the grids, in points.
+    pub emoji_size: i32,
+    /// Locales to search and show names in, best first; empty follows the environment.
+    pub locales: Vec<String>,
+    /// Whether the picker closes after copying an emoji (inserting always closes it).
+    pub close_on_copy: bool,
+    /// Whether picked emoji are copied to the clipboard or typed into the previous application.
+    pub output_mode: OutputMode,
+    /// Light or dark colors, or those of the system.
+    pub theme: Theme,
+    /// Whether categories are browsed one page at a time or in one continuous grid.
+    pub browse_layout: BrowseLayout,
+    /// Template for `--dmenu` lines; `None` uses `launcher::DEFAULT_LINE_TEMPLATE`.
+    pub line_template: Option<String>,
+    /// Skin tone applied when showing and copying emoji.
+    pub skin_tone: SkinTonePreference,
+    /// Newest emoji version shown, like `"13.0"`; `None` shows all of them.
+    pub max_emoji_version: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_COLUMNS,
+            rows: DEFAULT_ROWS,
+            emoji_size: DEFAULT_EMOJI_SIZE,
+            locales: Vec::new(),
+            close_on_copy: false,
+            output_mode: OutputMode::default(),
+            theme: Theme::default(),
+            browse_layout: BrowseLayout::default(),
+            line_template: None,
+            skin_tone: SkinTonePreference::default(),
+            max_emoji_version: None,
+        }
+    }
+}
+
+impl Settings {
+    /// These settings with sizes clamped to their allowed ranges, and without an emoji
+    /// version that does not parse.
+    pub fn normalized(self) -> Self {
+        let clamp = |value: i32, range: RangeInclusive<i32>| value.clamp(*range.start(), *range.end());
+        let max_emoji_version = self.max_emoji_version.filter(|version| {
+            let valid = parse_emoji_version(version).is_some();
+            if !valid {
+                log::warn!("Ignoring invalid max_emoji_version {:?}", version);
+            }
+            valid
+        });
+        Self {
+            columns: clamp(self.columns, COLUMNS_RANGE),
+            rows: clamp(self.rows, ROWS_RANGE),
+            emoji_size: clamp(self.emoji_size, EMOJI_SIZE_RANGE),
+            max_emoji_version,
+            ..self
+        }
+    }
+
+    /// The newest emoji version to show, as `(major, minor)`, if it is limited.
+    pub fn max_emoji_version(&self) -> Option<(u8, u8)> {
+        self.max_emoji_version.as_deref().and_then(parse_emoji_version)
+    }
+
+    /// The compiled-in locales to search, best first: the configured ones, or the user's.
+    pub fn search_locales(&self) -> Vec<&'static str> {
+        if self.locales.is_empty() {
+            locale::current().to_vec()
+        } else {
+            locale::resolve(self.locales.iter().map(String::as_str))
+        }
+    }
+
+    /// Parse settings from TOML text.
+    ///
+    /// Only text that is not TOML is an error. A value of the wrong type or with an unknown
+    /// choice (such as `theme = "blue"`) is left out with a warning, so it takes its default
+    /// and the other settings still apply.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = text.parse()?;
+        drop_invalid(&mut table, &[]);
+        toml::Value::Table(table).try_into()
+    }
+
+    /// Serialize the settings to TOML text.
+    pub fn to_text(&self) -> String {
+        toml::to_string(self).expect("settings are always representable as TOML")
+    }
+
+    /// Load settings from `path`. A missing file, or one that is not TOML, yields the defaults.
+    ///
+    /// A file that is not TOML is renamed to `settings.toml.invalid`, so saving the settings
+    /// later does not overwrite what the user wrote.
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map(Self::normalized).unwrap_or_else(|err| {
+                let kept = path.with_extension("toml.invalid");
+                log::warn!("Ignoring invalid settings {}, kept as {}: {}", path.display(), kept.display(), err);
+                if let Err(err) = fs::rename(path, &kept) {
+                    log::warn!("Could not rename invalid settings {}: {}", path.display(), err);
+                }
+                Self::default()
+            }),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Could not read settings {}: {}", path.display(), err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the settings to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never leaves truncated settings.
+        let tmp = path.with_extension("toml.tmp");
+        fs::write(&tmp, self.to_text())?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// Remove the values of `table`, found under the keys `path` of the settings, that [`Settings`]
+/// cannot take, with a warning for each. Tables are checked value by value, so one bad value in
+/// them (such as a tone in `[skin_tone.per_emoji]`) does not drop the others.
+fn drop_invalid(table: &mut toml::Table, path: &[&str]) {
+    table.retain(|key, value| {
+        let key_path: Vec<&str> = path.iter().copied().chain([key]).collect();
+        if let toml::Value::Table(inner) = value {
+            drop_invalid(inner, &key_path);
+        }
+        // The value alone, at its place in otherwise empty settings
+        let only = key_path
+            .iter()
+            .rev()
+            .fold(value.clone(), |inner, key| toml::Value::Table(toml::Table::from_iter([(key.to_string(), inner)])));
+        match only.try_into::<Settings>() {
+            Ok(_) => true,
+            Err(err) => {
+                log::warn!("Ignoring invalid setting {}: {}", key_path.join("."), err.message());
+                false
+            }
+        }
+    });
+}
+
+/// Parse an emoji version such as `"13.0"` or `"E13.0"` into `(major, minor)`.
+pub fn parse_emoji_version(text: &str) -> Option<(u8, u8)> {
+    let text = text.trim();
+    let (major, minor) = text.strip_prefix('E').unwrap_or(text).split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Default location of the settings file: `$XDG_CONFIG_HOME/emoji-picker/settings.toml`.
+pub fn default_path() -> PathBuf {
+    crate::paths::config_dir().join(SETTINGS_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::skin_tone::SkinTone;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut settings = Settings::default();
+        settings.skin_tone.default = SkinTone::MediumDark;
+        settings.skin_tone.set_for("👍", SkinTone::Light);
+        settings.theme = Theme::Dark;
+        settings.locales = vec!["nl".to_string()];
+        settings.line_template = Some("{emoji}".to_string());
+        let text = settings.to_text();
+        assert!(text.contains("medium-dark"), "{}", text);
+        assert_eq!(Settings::parse(&text).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_missing_keys_use_defaults() {
+        assert_eq!(Settings::parse("").unwrap(), Settings::default());
+        let settings = Settings::parse("[skin_tone]\ndefault = \"dark\"\n").unwrap();
+        assert_eq!(settings.skin_tone.default, SkinTone::Dark);
+        assert_eq!(settings.output_mode, OutputMode::Copy);
+        let settings = Settings::parse("output_mode = \"insert\"\n").unwrap();
+        assert_eq!(settings.output_mode, OutputMode::Insert);
+        assert!(settings.skin_tone.per_emoji.is_empty());
+        let settings = Settings::parse("[skin_tone]\ndefault = \"purple\"\n").unwrap();
+        assert_eq!(settings.skin_tone.default, SkinTone::Default);
+        let settings = Settings::parse("browse_layout = \"continuous\"\n").unwrap();
+        assert_eq!(settings.browse_layout, BrowseLayout::Continuous);
+        assert_eq!(settings.max_emoji_version(), None);
+        let settings = Settings::parse("max_emoji_version = \"13.1\"\n").unwrap();
+        assert_eq!(settings.max_emoji_version(), Some((13, 1)));
+    }
+
+    #[test]
+    fn test_invalid_values_are_dropped_one_by_one() {
+        let text = "\
+columns = \"wide\"
+rows = 8
+theme = \"blue\"
+output_mode = \"insert\"
+[skin_tone]
+default = \"dark\"
+[skin_tone.per_emoji]
+\"👍\" = \"light\"
+\"👋\" = \"purple\"
+";
+        let settings = Settings::parse(text).unwrap();
+        assert_eq!(settings.columns, DEFAULT_COLUMNS);
+        assert_eq!(settings.rows, 8);
+        assert_eq!(settings.theme, Theme::System);
+        assert_eq!(settings.output_mode, OutputMode::Insert);
+        assert_eq!(settings.skin_tone.default, SkinTone::Dark);
+        assert_eq!(settings.skin_tone.tone_for("👍"), SkinTone::Light);
+        assert_eq!(settings.skin_tone.tone_for("👋"), SkinTone::Dark);
+        assert!(Settings::parse("not = [valid").is_err());
+    }
+
+    #[test]
+    fn test_parse_emoji_version() {
+        assert_eq!(parse_emoji_version("E0.6"), Some((0, 6)));
+        assert_eq!(parse_emoji_version(" 15.0 "), Some((15, 0)));
+        assert_eq!(parse_emoji_version("15"), None);
+        assert_eq!(parse_emoji_version("E15.x"), None);
+    }
+
+    #[test]
+    fn test_sizes_are_clamped_on_load() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-settings-clamp-{}", std::process::id()));
+        let path = dir.join(SETTINGS_FILE);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "columns = 100\nrows = 0\nemoji_size = 40\n").unwrap();
+        let settings = Settings::load(&path);
+        assert_eq!(settings.columns, *COLUMNS_RANGE.end());
+        assert_eq!(settings.rows, *ROWS_RANGE.start());
+        assert_eq!(settings.emoji_size, 40);
+        fs::write(&path, "max_emoji_version = \"thirteen\"\n").unwrap();
+        assert_eq!(Settings::load(&path).max_emoji_version, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_configured_locales_replace_the_environment() {
+        let settings = Settings { locales: vec!["nl_BE".to_string()], ..Default::default() };
+        assert_eq!(settings.search_locales(), vec!["nl", "en"]);
+        assert_eq!(Settings::default().search_locales(), locale::current());
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-settings-{}", std::process::id()));
+        let path = dir.join("nested").join(SETTINGS_FILE);
+        assert_eq!(Settings::load(&path), Settings::default());
+        let mut settings = Settings::default();
+        settings.skin_tone.default = SkinTone::Medium;
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path), settings);
+        fs::write(&path, "not = [valid").unwrap();
+        assert_eq!(Settings::load(&path), Settings::default());
+        settings.save(&path).unwrap();
+        let kept = path.with_extension("toml.invalid");
+        assert_eq!(fs::read_to_string(kept).unwrap(), "not = [valid", "saving keeps the invalid file");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
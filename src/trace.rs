@@ -0,0 +1,60 @@
+//! Startup trace, for checking how fast the picker comes up.
+//!
+//! With `EMOJI_PICKER_TRACE=1` in the environment, [`mark`] prints the time since the process
+//! started to stderr, e.g. `emoji-picker trace:      83.4 ms  first frame`. It does not need
+//! `RUST_LOG`, and costs nothing when the variable is unset.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Environment variable that turns the trace on.
+pub const TRACE_VAR: &str = "EMOJI_PICKER_TRACE";
+
+static START: OnceLock<Instant> = OnceLock::new();
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Whether `value` of [`TRACE_VAR`] turns the trace on: anything but unset, empty or `0`.
+pub fn is_enabled_by(value: Option<&str>) -> bool {
+    !matches!(value.map(str::trim), None | Some("") | Some("0"))
+}
+
+/// Whether the trace is on.
+pub fn enabled() -> bool {
+    *ENABLED.get_or_init(|| is_enabled_by(std::env::var(TRACE_VAR).ok().as_deref()))
+}
+
+/// Start the clock; call first thing. Later calls keep the first start.
+pub fn start() {
+    START.get_or_init(Instant::now);
+}
+
+/// A trace line for `event`, `elapsed` after the start.
+pub fn format_mark(elapsed: Duration, event: &str) -> String {
+    format!("emoji-picker trace: {:>9.1} ms  {}", elapsed.as_secs_f64() * 1000.0, event)
+}
+
+/// Print the time since [`start`] with `event`, when the trace is on.
+pub fn mark(event: &str) {
+    if enabled() {
+        let elapsed = START.get().map(Instant::elapsed).unwrap_or_default();
+        eprintln!("{}", format_mark(elapsed, event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_variable_and_format() {
+        assert!(!is_enabled_by(None));
+        assert!(!is_enabled_by(Some("")));
+        assert!(!is_enabled_by(Some("0")));
+        assert!(is_enabled_by(Some("1")));
+        assert!(is_enabled_by(Some("yes")));
+        assert_eq!(
+            format_mark(Duration::from_micros(83_420), "first frame"),
+            "emoji-picker trace:      83.4 ms  first frame"
+        );
+    }
+}
@@ -1,7 +1,17 @@
 //! Central controller and state for the emoji picker UI.
 //! Owns all UI components and coordinates their interaction via callbacks/signals.
 
-use crate::emoji::emoji_data::Emoji;
+use crate::emoji::coverage::Coverage;
+use crate::emoji::emoji_data::{Emoji, Status, EMOJIS, OTHER_FORMS};
+use crate::emoji::favorites::Favorites;
+use crate::emoji::index;
+use crate::emoji::locale;
+use crate::emoji::search;
+use crate::emoji::skin_tone::SkinTone;
+use crate::emoji::unicode;
+use crate::emoji::usage::{self, UsageHistory};
+use crate::settings::Settings;
+use std::path::PathBuf;
 
 /// The current mode of the picker UI.
 /// The current mode of the picker UI.
@@ -13,13 +23,49 @@ pub enum PickerMode {
     Search,
 }
 
+/// The characters the picker offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Catalog {
+    /// Emoji, kaomoji and user snippets, browsed by category.
+    #[default]
+    Emoji,
+    /// Any named Unicode character, browsed by block and searched by name or codepoint.
+    Unicode,
+}
+
+/// Callback for picked emoji, see [`EmojiPickerController::add_pick_listener`].
+type PickListener = Box<dyn Fn(&str)>;
+
 /// Centralized state and controller for the emoji picker UI, pure and testable.
 pub struct EmojiPickerController {
     pub mode: PickerMode,
+    /// What is browsed and searched.
+    pub catalog: Catalog,
     pub search_query: String,
     pub all_emojis: Vec<Emoji>,
     pub filtered_emojis: Vec<Emoji>,
+    /// Locales whose names and keywords are searched, best first. Defaults to the user's locales.
+    pub locales: Vec<&'static str>,
+    /// Emoji picked so far, most recent first. Backs the "Recent" page.
+    pub usage: UsageHistory,
+    /// Where `usage` is persisted after every pick; `None` keeps it in memory only.
+    usage_path: Option<PathBuf>,
+    /// Emoji pinned by the user, in their order. Backs the "Favorites" page.
+    pub favorites: Favorites,
+    /// Where `favorites` are saved after every change; `None` keeps them in memory only.
+    favorites_path: Option<PathBuf>,
+    /// User settings, such as the preferred skin tone.
+    pub settings: Settings,
+    /// Where `settings` are saved after every change; `None` keeps them in memory only.
+    settings_path: Option<PathBuf>,
+    /// Which emoji the fonts can show; the others are left out of browsing and search.
+    pub coverage: Coverage,
+    /// Whether `all_emojis` starts with the built-in table, so searches can use the
+    /// precomputed index for it and only scan the rest (such as user snippets).
+    indexed: bool,
     listeners: Vec<Box<dyn Fn(PickerMode, &[Emoji])>>, // Observer pattern
+    /// Called with every picked emoji, as delivered (including its skin tone).
+    pick_listeners: Vec<PickListener>,
 }
 
 impl EmojiPickerController {
@@ -28,6 +74,17 @@ impl EmojiPickerController {
         self.listeners.push(Box::new(f));
     }
 
+    /// Register a callback to be called whenever an emoji is picked.
+    pub fn add_pick_listener<F: Fn(&str) + 'static>(&mut self, f: F) {
+        self.pick_listeners.push(Box::new(f));
+    }
+
+    /// Remove the listeners added with [`EmojiPickerController::add_listener`], e.g. before
+    /// the widgets they update are replaced.
+    pub fn clear_listeners(&mut self) {
+        self.listeners.clear();
+    }
+
     fn notify_listeners(&self) {
         for cb in &self.listeners {
             cb(self.mode, &self.filtered_emojis);
@@ -38,15 +95,219 @@ impl EmojiPickerController {
         matches!(self.mode, PickerMode::Browse)
     }
     pub fn new(all_emojis: Vec<Emoji>) -> Self {
+        let indexed = index::covers(&all_emojis);
         Self {
             mode: PickerMode::Browse,
+            catalog: Catalog::default(),
             search_query: String::new(),
             all_emojis,
             filtered_emojis: Vec::new(),
+            locales: locale::current().to_vec(),
+            usage: UsageHistory::default(),
+            usage_path: None,
+            favorites: Favorites::default(),
+            favorites_path: None,
+            settings: Settings::default(),
+            settings_path: None,
+            coverage: Coverage::default(),
+            indexed,
             listeners: Vec::new(), // Initialize listeners
+            pick_listeners: Vec::new(),
+        }
+    }
+
+    /// Create a controller whose usage history is loaded from and saved to `usage_path`.
+    pub fn with_usage(all_emojis: Vec<Emoji>, usage: UsageHistory, usage_path: Option<PathBuf>) -> Self {
+        Self {
+            usage,
+            usage_path,
+            ..Self::new(all_emojis)
+        }
+    }
+
+    /// Use `settings`, saving every change to `settings_path`.
+    pub fn with_settings(self, settings: Settings, settings_path: Option<PathBuf>) -> Self {
+        Self {
+            locales: settings.search_locales(),
+            settings,
+            settings_path,
+            ..self
+        }
+    }
+
+    /// Use `favorites`, saving every change to `favorites_path`.
+    pub fn with_favorites(self, favorites: Favorites, favorites_path: Option<PathBuf>) -> Self {
+        Self {
+            favorites,
+            favorites_path,
+            ..self
+        }
+    }
+
+    /// Leave the emoji out that `coverage` says the fonts cannot show.
+    pub fn with_coverage(self, coverage: Coverage) -> Self {
+        Self { coverage, ..self }
+    }
+
+    /// Change the settings with `update` and save them.
+    ///
+    /// Settings the controller uses itself take effect right away: a running search is redone
+    /// with the new locales, which notifies the listeners.
+    pub fn update_settings<F: FnOnce(&mut Settings)>(&mut self, update: F) {
+        update(&mut self.settings);
+        self.settings = std::mem::take(&mut self.settings).normalized();
+        self.locales = self.settings.search_locales();
+        self.save_settings();
+        if self.mode == PickerMode::Search {
+            let query = self.search_query.clone();
+            self.handle_search(&query);
+        }
+    }
+
+    /// Switch to `catalog`, redoing the current search (or browsing) in it.
+    pub fn set_catalog(&mut self, catalog: Catalog) {
+        self.catalog = catalog;
+        let query = self.search_query.clone();
+        self.handle_search(&query);
+    }
+
+    /// Set the skin tone used for every emoji without a tone of its own.
+    pub fn set_skin_tone(&mut self, tone: SkinTone) {
+        self.settings.skin_tone.default = tone;
+        self.save_settings();
+    }
+
+    /// Set the skin tone of the emoji whose default form is `ch`.
+    pub fn set_emoji_skin_tone(&mut self, ch: &str, tone: SkinTone) {
+        self.settings.skin_tone.set_for(ch, tone);
+        self.save_settings();
+    }
+
+    fn save_settings(&self) {
+        if let Some(ref path) = self.settings_path {
+            if let Err(err) = self.settings.save(path) {
+                log::warn!("Could not save settings to {}: {}", path.display(), err);
+            }
         }
     }
 
+    /// Record that `ch` was picked (copied) and persist the usage history.
+    ///
+    /// Skin tone variants are recorded as their default form, so they share one history entry.
+    pub fn record_pick(&mut self, ch: &str) {
+        for cb in &self.pick_listeners {
+            cb(ch);
+        }
+        let ch = self.lookup(ch).map_or(ch, |e| e.ch);
+        self.usage.record(ch, usage::now());
+        if let Some(ref path) = self.usage_path {
+            if let Err(err) = self.usage.save(path) {
+                log::warn!("Could not save usage history to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// Whether `ch` (or the emoji it is a skin tone variant of) is a favorite.
+    pub fn is_favorite(&self, ch: &str) -> bool {
+        self.favorites.contains(self.lookup(ch).map_or(ch, |e| e.ch))
+    }
+
+    /// Pin `ch` to the favorites, or unpin it if it is one, and save them. Returns whether it
+    /// is a favorite now. Skin tone variants are pinned as their default form.
+    pub fn toggle_favorite(&mut self, ch: &str) -> bool {
+        let ch = self.lookup(ch).map_or(ch, |e| e.ch).to_string();
+        let pinned = self.favorites.toggle(&ch);
+        self.save_favorites();
+        pinned
+    }
+
+    /// Move the favorite `ch` by `offset` places and save the favorites. Returns its new
+    /// position, or `None` if it is not a favorite.
+    pub fn move_favorite(&mut self, ch: &str, offset: isize) -> Option<usize> {
+        let ch = self.lookup(ch).map_or(ch, |e| e.ch).to_string();
+        let position = self.favorites.move_by(&ch, offset)?;
+        self.save_favorites();
+        Some(position)
+    }
+
+    fn save_favorites(&self) {
+        if let Some(ref path) = self.favorites_path {
+            if let Err(err) = self.favorites.save(path) {
+                log::warn!("Could not save favorites to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /// The favorites, in their order, for the "Favorites" page. Besides emoji these can be
+    /// Unicode characters; anything else (e.g. a removed snippet) is skipped.
+    pub fn favorite_emojis(&self) -> Vec<Emoji> {
+        self.favorites
+            .entries()
+            .iter()
+            .filter_map(|ch| {
+                let character = || {
+                    let mut chars = ch.chars();
+                    let codepoint = chars.next().filter(|_| chars.next().is_none())?;
+                    unicode::character(codepoint.into())
+                };
+                self.all_emojis.iter().find(|e| e.ch == ch).or_else(character)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The emoji `ch`, or the emoji that has `ch` as one of its skin tone variants, or else
+    /// its other form (see `OTHER_FORMS`), such as `☺` without its presentation selector.
+    pub fn lookup(&self, ch: &str) -> Option<&Emoji> {
+        self.all_emojis
+            .iter()
+            .find(|e| e.ch == ch)
+            .or_else(|| self.all_emojis.iter().find(|e| e.skin_tone_variants.is_some_and(|v| v.contains(&ch))))
+            .or_else(|| OTHER_FORMS.iter().find(|e| e.ch == ch))
+    }
+
+    /// The categories of `all_emojis`: the built-in ones sorted, then any others (such as
+    /// those of user snippets) in the order they first appear.
+    pub fn categories(&self) -> Vec<&'static str> {
+        let builtin = if self.indexed { EMOJIS.len() } else { 0 };
+        let mut categories: Vec<&'static str> = self.all_emojis[..builtin].iter().map(|e| e.category).collect();
+        categories.sort();
+        categories.dedup();
+        for emoji in &self.all_emojis[builtin..] {
+            if !categories.contains(&emoji.category) {
+                categories.push(emoji.category);
+            }
+        }
+        categories
+    }
+
+    /// Whether `emoji` is browsed and found: it is fully-qualified, the fonts can show it, and
+    /// it is no newer than the `max_emoji_version` setting.
+    pub fn is_shown(&self, emoji: &Emoji) -> bool {
+        emoji.status == Status::FullyQualified
+            && self.coverage.is_supported(emoji.ch)
+            && self.settings.max_emoji_version().filter(|&max| emoji.version > max).is_none()
+    }
+
+    /// The emoji of `all_emojis` that are shown (see [`EmojiPickerController::is_shown`]).
+    pub fn shown_emojis(&self) -> Vec<Emoji> {
+        self.all_emojis.iter().filter(|e| self.is_shown(e)).cloned().collect()
+    }
+
+    /// The recently picked emoji, most recent first, for the "Recent" page.
+    ///
+    /// History entries that are not part of `all_emojis` (e.g. from an older emoji set) are skipped,
+    /// as are emoji that are not shown.
+    pub fn recent_emojis(&self) -> Vec<Emoji> {
+        self.usage
+            .entries()
+            .iter()
+            .filter_map(|entry| self.all_emojis.iter().find(|e| e.ch == entry.ch))
+            .filter(|e| self.is_shown(e))
+            .cloned()
+            .collect()
+    }
+
     /// Handle a search query update. Switches mode and updates filtered results.
     pub fn handle_search(&mut self, query: &str) {
         log::info!(
@@ -62,20 +323,10 @@ impl EmojiPickerController {
         } else {
             log::info!("Switching to Search mode (query: '{}')", query);
             self.mode = PickerMode::Search;
-            let q = query.to_lowercase();
-            let filtered: Vec<_> = self
-                .all_emojis
-                .iter()
-                .filter(|e| {
-                    e.name_en.to_lowercase().contains(&q)
-                        || e.keywords_en.iter().any(|k| k.to_lowercase().contains(&q))
-                        || e.name_nl.to_lowercase().contains(&q)
-                        || e.keywords_nl.iter().any(|k| k.to_lowercase().contains(&q))
-                        || e.ch.contains(&q)
-                })
-                .cloned()
-                .collect();
-            self.filtered_emojis = filtered;
+            self.filtered_emojis = match self.catalog {
+                Catalog::Emoji => self.rank(query),
+                Catalog::Unicode => unicode::search(query).into_iter().cloned().collect(),
+            };
         }
         log::info!(
             "After search, mode is now: {:?}, filtered_emojis: {}",
@@ -86,6 +337,21 @@ impl EmojiPickerController {
         self.notify_listeners(); // Notify listeners after handling search
     }
 
+    /// The emoji matching `query`, best first, without changing the picker state.
+    ///
+    /// Best text matches come first, lifted by how often and how recently they were picked.
+    /// Emoji that are not shown are left out.
+    pub fn rank(&self, query: &str) -> Vec<Emoji> {
+        let now = usage::now();
+        let ranked = if self.indexed {
+            let extra = &self.all_emojis[EMOJIS.len()..];
+            search::rank_indexed_with(extra, query, &self.locales, &self.usage, now)
+        } else {
+            search::rank(&self.all_emojis, query, &self.locales, &self.usage, now)
+        };
+        ranked.into_iter().filter(|e| self.is_shown(e)).cloned().collect()
+    }
+
     pub fn handle_category_selected(&mut self, _category_idx: usize) {
         // In real UI, would update grid
     }
@@ -98,13 +364,17 @@ impl EmojiPickerController {
 #[cfg(test)]
 #[test]
 fn test_show_category_bar_logic() {
+    use crate::emoji::emoji_data::Localized;
     let emojis = vec![crate::emoji::emoji_data::Emoji {
         ch: "😀",
-        name_en: "grinning face",
-        keywords_en: &[],
-        name_nl: "grijnzend gezicht",
-        keywords_nl: &[],
+        localized: &[
+            Localized { locale: "en", name: "grinning face", keywords: &[] },
+            Localized { locale: "nl", name: "grijnzend gezicht", keywords: &[] },
+        ],
         category: "Smileys & Emotion",
+        subgroup: "face-smiling",
+        version: (1, 0),
+        status: crate::emoji::emoji_data::Status::FullyQualified,
         skin_tone_variants: None,
     }];
     let mut controller = EmojiPickerController::new(emojis.clone());
@@ -117,6 +387,7 @@ fn test_show_category_bar_logic() {
     controller.handle_search("");
     assert!(controller.show_category_bar());
 }
+#[cfg(test)]
 mod tests {
         #[test]
         fn test_listener_callback_receives_correct_state() {
@@ -127,19 +398,23 @@ mod tests {
             let called_clone = Rc::clone(&called);
             controller.add_listener(move |mode, emojis| {
                 *called_clone.borrow_mut() = true;
-                // Should be in Search mode and filtered_emojis should match query
+                // Should be in Search mode and the best match should contain the query
                 assert_eq!(mode, PickerMode::Search);
-                assert!(emojis.iter().all(|e| e.name_en.contains("joy") || e.keywords_en.iter().any(|k| k.contains("joy"))));
+                let best = &emojis[0];
+                assert!(best.localized.iter().any(|l| l.name.contains("joy") || l.keywords.iter().any(|k| k.contains("joy"))));
             });
             controller.handle_search("joy");
             assert!(*called.borrow(), "Listener callback was not called");
         }
     use super::*;
-    use crate::emoji::emoji_data::EMOJIS;
+    use crate::emoji::emoji_data::{EMOJIS, LOCALES};
 
     fn make_controller() -> EmojiPickerController {
         let all_emojis = EMOJIS.to_vec();
-        EmojiPickerController::new(all_emojis)
+        let mut controller = EmojiPickerController::new(all_emojis);
+        // Search every compiled-in locale, whatever the environment says
+        controller.locales = LOCALES.to_vec();
+        controller
     }
 
     #[test]
@@ -156,14 +431,16 @@ mod tests {
         controller.handle_search("smile");
         assert_eq!(controller.mode, PickerMode::Search);
         assert_eq!(controller.search_query, "smile");
-        assert!(controller
-            .filtered_emojis
+        // Fuzzy matching may add more results, but every literal match must be included
+        let contains_smile = |e: &Emoji| {
+            e.localized.iter().any(|l| l.name.contains("smile") || l.keywords.iter().any(|k| k.contains("smile")))
+                || e.ch.contains("smile")
+        };
+        assert!(contains_smile(&controller.filtered_emojis[0]));
+        assert!(EMOJIS
             .iter()
-            .all(|e| e.name_en.contains("smile")
-                || e.keywords_en.iter().any(|k| k.contains("smile"))
-                || e.name_nl.contains("smile")
-                || e.keywords_nl.iter().any(|k| k.contains("smile"))
-                || e.ch.contains("smile")));
+            .filter(|e| contains_smile(e))
+            .all(|e| controller.filtered_emojis.iter().any(|f| f.ch == e.ch)));
     }
 
     #[test]
@@ -194,6 +471,231 @@ mod tests {
         assert_eq!(controller.mode, PickerMode::Search);
         assert!(!controller.filtered_emojis.is_empty());
     }
+
+    #[test]
+    fn test_search_matches_words_in_any_order() {
+        let mut controller = make_controller();
+        controller.handle_search("heart red");
+        assert_eq!(controller.filtered_emojis[0].ch, "❤️");
+        controller.handle_search("red heart");
+        assert_eq!(controller.filtered_emojis[0].ch, "❤️");
+        controller.handle_search("unicorn pizza");
+        assert!(controller.filtered_emojis.is_empty());
+    }
+
+    #[test]
+    fn test_search_uses_the_controller_locales() {
+        let mut controller = make_controller();
+        controller.locales = vec!["nl", "en"];
+        controller.handle_search("koffie");
+        assert_eq!(controller.filtered_emojis[0].ch, "☕");
+        controller.locales = vec!["en"];
+        controller.handle_search("koffie");
+        assert!(controller.filtered_emojis.iter().all(|e| e.ch != "☕"));
+    }
+
+    #[test]
+    fn test_record_pick_updates_recent_emojis() {
+        let mut controller = make_controller();
+        assert!(controller.recent_emojis().is_empty());
+        controller.record_pick("👍");
+        controller.record_pick("😀");
+        controller.record_pick("👍");
+        controller.record_pick("not an emoji");
+        let recent: Vec<_> = controller.recent_emojis().iter().map(|e| e.ch).collect();
+        assert_eq!(recent, vec!["👍", "😀"]);
+        assert_eq!(controller.usage.get("👍").unwrap().count, 2);
+    }
+
+    #[test]
+    fn test_skin_tone_picks_are_recorded_as_the_default_form() {
+        let mut controller = make_controller();
+        controller.record_pick("👍🏽");
+        controller.record_pick("👍");
+        controller.record_pick("👋🏿");
+        let recent: Vec<_> = controller.recent_emojis().iter().map(|e| e.ch).collect();
+        assert_eq!(recent, vec!["👋", "👍"]);
+        assert_eq!(controller.usage.get("👍").unwrap().count, 2);
+    }
+
+    #[test]
+    fn test_pick_listeners_receive_the_picked_form() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let mut controller = make_controller();
+        let picked = Rc::new(RefCell::new(Vec::new()));
+        let picked_clone = picked.clone();
+        controller.add_pick_listener(move |ch| picked_clone.borrow_mut().push(ch.to_string()));
+        controller.record_pick("👍🏽");
+        assert_eq!(*picked.borrow(), vec!["👍🏽"]);
+    }
+
+    #[test]
+    fn test_rank_leaves_the_picker_state_alone() {
+        let mut controller = make_controller();
+        controller.handle_search("cat");
+        let ranked = controller.rank("tada");
+        assert_eq!(ranked[0].ch, "🎉");
+        assert_eq!(controller.search_query, "cat");
+        assert_ne!(controller.filtered_emojis[0].ch, "🎉");
+    }
+
+    #[test]
+    fn test_snippets_are_searched_and_get_their_own_category() {
+        let snippets = crate::emoji::collections::parse(
+            "[[snippet]]\ntext = \"¯\\\\_(ツ)_/¯\"\nname = \"shrug\"\nkeywords = [\"teamshrug\"]\n",
+        )
+        .unwrap();
+        let mut all_emojis = EMOJIS.to_vec();
+        all_emojis.extend(snippets);
+        let mut controller = EmojiPickerController::new(all_emojis);
+        controller.handle_search("teamshrug");
+        assert_eq!(controller.filtered_emojis[0].ch, "¯\\_(ツ)_/¯");
+        controller.record_pick("¯\\_(ツ)_/¯");
+        assert_eq!(controller.recent_emojis()[0].ch, "¯\\_(ツ)_/¯");
+        let categories = controller.categories();
+        assert_eq!(categories.last(), Some(&crate::emoji::collections::CUSTOM_CATEGORY));
+        assert_eq!(categories.len(), make_controller().categories().len() + 1);
+    }
+
+    #[test]
+    fn test_unicode_catalog_searches_characters() {
+        let mut controller = make_controller();
+        controller.handle_search("rightwards arrow");
+        assert!(controller.filtered_emojis.iter().all(|e| e.ch != "→"));
+        controller.set_catalog(Catalog::Unicode);
+        assert_eq!(controller.mode, PickerMode::Search);
+        assert_eq!(controller.filtered_emojis[0].ch, "→");
+        controller.handle_search("U+2014");
+        assert_eq!(controller.filtered_emojis[0].ch, "—");
+        controller.set_catalog(Catalog::Emoji);
+        assert!(controller.filtered_emojis.iter().all(|e| e.ch != "—"));
+        controller.handle_search("");
+        controller.set_catalog(Catalog::Unicode);
+        assert_eq!(controller.mode, PickerMode::Browse);
+    }
+
+    #[test]
+    fn test_favorites_are_pinned_reordered_and_saved() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-controller-favorites-{}", std::process::id()));
+        let path = dir.join(crate::emoji::favorites::FAVORITES_FILE);
+        let mut controller = make_controller().with_favorites(Favorites::default(), Some(path.clone()));
+        assert!(controller.toggle_favorite("🎉"));
+        assert!(controller.toggle_favorite("👍🏽"));
+        assert!(controller.toggle_favorite("→"));
+        assert!(controller.is_favorite("👍"));
+        assert!(controller.is_favorite("👍🏿"));
+        let favorites: Vec<_> = controller.favorite_emojis().iter().map(|e| e.ch).collect();
+        assert_eq!(favorites, vec!["🎉", "👍", "→"]);
+
+        assert_eq!(controller.move_favorite("→", -2), Some(0));
+        assert_eq!(controller.move_favorite("😀", 1), None);
+        assert!(!controller.toggle_favorite("🎉"));
+        let favorites: Vec<_> = controller.favorite_emojis().iter().map(|e| e.ch).collect();
+        assert_eq!(favorites, vec!["→", "👍"]);
+        assert_eq!(Favorites::load(&path), controller.favorites);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_kaomoji_are_searched_with_the_emoji() {
+        let mut all_emojis = EMOJIS.to_vec();
+        all_emojis.extend_from_slice(crate::emoji::kaomoji::KAOMOJI);
+        let mut controller = EmojiPickerController::new(all_emojis);
+        controller.handle_search("table flip");
+        assert_eq!(controller.filtered_emojis[0].ch, "(╯°□°)╯︵ ┻━┻");
+        controller.handle_search("cat");
+        assert!(controller.filtered_emojis.iter().any(|e| e.ch == "🐈"));
+        assert!(controller.filtered_emojis.iter().any(|e| e.ch == "=^.^="));
+        let categories = controller.categories();
+        assert_eq!(categories.last(), Some(&crate::emoji::kaomoji::KAOMOJI_CATEGORY));
+    }
+
+    #[test]
+    fn test_skin_tone_changes_are_saved() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-controller-{}", std::process::id()));
+        let path = dir.join("settings.toml");
+        let mut controller = make_controller().with_settings(Settings::default(), Some(path.clone()));
+        controller.set_skin_tone(SkinTone::Medium);
+        controller.set_emoji_skin_tone("👍", SkinTone::Dark);
+        let saved = Settings::load(&path);
+        assert_eq!(saved.skin_tone.default, SkinTone::Medium);
+        assert_eq!(saved.skin_tone.tone_for("👍"), SkinTone::Dark);
+        assert_eq!(saved.skin_tone.tone_for("👋"), SkinTone::Medium);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_update_settings_applies_locales_and_saves() {
+        let dir = std::env::temp_dir().join(format!("emoji-picker-controller-update-{}", std::process::id()));
+        let path = dir.join("settings.toml");
+        let mut controller = make_controller().with_settings(Settings::default(), Some(path.clone()));
+        controller.handle_search("koffie");
+        controller.update_settings(|s| s.locales = vec!["en".to_string()]);
+        assert_eq!(controller.locales, vec!["en"]);
+        assert!(controller.filtered_emojis.iter().all(|e| e.ch != "☕"));
+        controller.update_settings(|s| {
+            s.locales = vec!["nl".to_string()];
+            s.columns = 1000;
+        });
+        assert_eq!(controller.filtered_emojis[0].ch, "☕");
+        let saved = Settings::load(&path);
+        assert_eq!(saved.locales, vec!["nl"]);
+        assert_eq!(saved.columns, *crate::settings::COLUMNS_RANGE.end());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recent_emojis_respect_history_capacity() {
+        let mut controller = EmojiPickerController::with_usage(EMOJIS.to_vec(), UsageHistory::new(2), None);
+        for e in EMOJIS.iter().take(5) {
+            controller.record_pick(e.ch);
+        }
+        let recent: Vec<_> = controller.recent_emojis().iter().map(|e| e.ch).collect();
+        assert_eq!(recent, vec![EMOJIS[4].ch, EMOJIS[3].ch]);
+    }
+
+    #[test]
+    fn test_search_ranks_recently_picked_emoji_first() {
+        let mut controller = make_controller();
+        controller.record_pick("🫀");
+        controller.handle_search("heart");
+        assert_eq!(controller.filtered_emojis[0].ch, "🫀");
+        for _ in 0..3 {
+            controller.record_pick("❤️");
+        }
+        controller.handle_search("heart");
+        assert_eq!(controller.filtered_emojis[0].ch, "❤️");
+    }
+
+    #[test]
+    fn test_other_forms_are_looked_up_but_not_shown() {
+        let controller = make_controller();
+        let unqualified = controller.lookup("☺").unwrap();
+        assert_eq!((unqualified.ch, unqualified.status), ("☺", Status::Unqualified));
+        assert_eq!(unqualified.name(&["en"]), "smiling face");
+        assert!(!controller.is_shown(unqualified));
+        assert_eq!(controller.lookup("🏽").unwrap().status, Status::Component);
+        assert_eq!(controller.lookup("☺️").unwrap().status, Status::FullyQualified);
+        assert!(OTHER_FORMS.iter().all(|e| e.status != Status::FullyQualified));
+    }
+
+    #[test]
+    fn test_unsupported_and_newer_emoji_are_not_shown() {
+        let coverage = Coverage::from_unsupported(["🫠"]);
+        let mut controller = make_controller().with_coverage(coverage);
+        controller.handle_search("melting face");
+        assert!(controller.filtered_emojis.iter().all(|e| e.ch != "🫠"));
+        assert_eq!(controller.shown_emojis().len(), EMOJIS.len() - 1);
+
+        controller.update_settings(|settings| settings.max_emoji_version = Some("13.0".to_string()));
+        controller.handle_search("shaking face");
+        assert!(controller.filtered_emojis.iter().all(|e| e.version <= (13, 0)));
+        assert!(controller.shown_emojis().iter().any(|e| e.ch == "🫀"));
+        assert!(!controller.shown_emojis().iter().any(|e| e.ch == "🫨"));
+        controller.record_pick("🥹");
+        assert!(controller.recent_emojis().is_empty());
+    }
 }
 
 /*
@@ -1,5 +1,9 @@
 use gtk4::prelude::*;
 use gtk4::{Box as GtkBox, Orientation, Button, Align};
+use crate::emoji::kaomoji::KAOMOJI_CATEGORY;
+use crate::ui::constants::{
+    CUSTOM_TAB_EMOJI, FAVORITES_CATEGORY, FAVORITES_TAB_EMOJI, KAOMOJI_TAB_EMOJI, RECENT_CATEGORY, RECENT_TAB_EMOJI,
+};
 use crate::ui::emoji_label::EmojiLabel;
 use std::rc::Rc;
 use std::cell::RefCell;
@@ -13,11 +17,14 @@ use gtk4::gdk;
 /// This widget does not implement scrolling itself. If you want the category bar to be horizontally scrollable,
 /// wrap `CategoryBar::button_bar` in a `gtk4::ScrolledWindow` with `hscrollbar_policy(PolicyType::Always)`.
 /// This allows clients to control layout and scrolling behavior as needed.
+#[derive(Clone)]
 pub struct CategoryBar {
     /// The horizontal box containing the emoji tab buttons.
     pub button_bar: GtkBox,
     pub selected_index: Rc<RefCell<Option<usize>>>,
     pub buttons: Rc<RefCell<Vec<Button>>>,
+    /// The category of each button.
+    pub categories: Vec<String>,
     pub on_category_selected: Rc<RefCell<Option<Box<dyn Fn(usize) + 'static>>>>,
     pub on_focus_emoji_grid: Rc<RefCell<Option<Box<dyn Fn()>>>>,
 }
@@ -27,11 +34,13 @@ impl CategoryBar {
     ///
     /// # Arguments
     /// * `categories` - List of category names.
-    /// * `stack` - The Stack widget to switch visible emoji grids.
+    /// * `show` - Shows the page or section of the category of a clicked tab.
     /// * `grid_width` - The width to constrain the tab bar and viewport.
     ///
-    /// The tab buttons use the first emoji of each category as their label.
-    pub fn new(categories: &[&str], stack: &gtk4::Stack, _grid_width: i32) -> Self {
+    /// The tab buttons use the first emoji of each category as their label, except for the
+    /// synthetic "Recent" page which gets a fixed clock tab, and categories of user snippets,
+    /// which share a fixed tab. Each tab has the category name as tooltip.
+    pub fn new(categories: &[&str], show: impl Fn(&str) + 'static, _grid_width: i32) -> Self {
         let button_bar = GtkBox::builder()
             .orientation(Orientation::Horizontal)
             .spacing(0)
@@ -40,21 +49,28 @@ impl CategoryBar {
             .build();
         use crate::emoji::emoji_data::EMOJIS;
         let buttons = Rc::new(RefCell::new(Vec::new()));
+        let show = Rc::new(show);
         for &category in categories.iter() {
-            // Find the first emoji in this category
-            let tab_emoji = EMOJIS.iter().find(|e| e.category == category).map(|e| e.ch).unwrap_or("?");
+            // Synthetic pages have a fixed tab; otherwise use the first emoji in this category
+            let tab_emoji = if category == FAVORITES_CATEGORY {
+                FAVORITES_TAB_EMOJI
+            } else if category == RECENT_CATEGORY {
+                RECENT_TAB_EMOJI
+            } else if category == KAOMOJI_CATEGORY {
+                KAOMOJI_TAB_EMOJI
+            } else {
+                EMOJIS.iter().find(|e| e.category == category).map(|e| e.ch).unwrap_or(CUSTOM_TAB_EMOJI)
+            };
             let tab_label = EmojiLabel::new(tab_emoji);
             tab_label.set_css_classes(&["emoji-label", "tab-emoji"]);
             // Set dynamic size properties for tab labels
             tab_label.set_width_request(_grid_width / categories.len() as i32);
             tab_label.set_height_request(_grid_width / categories.len() as i32);
             // Note: gtk4::Label does not have set_font_size, so use Pango attributes if needed for font size
-            let button = Button::builder().child(&tab_label).build();
-            let stack_clone = stack.clone();
+            let button = Button::builder().child(&tab_label).tooltip_text(category).build();
+            let show = show.clone();
             let cat_name = category.to_string();
-            button.connect_clicked(move |_| {
-                stack_clone.set_visible_child_name(&cat_name);
-            });
+            button.connect_clicked(move |_| show(&cat_name));
             button_bar.append(&button);
             buttons.borrow_mut().push(button);
         }
@@ -65,6 +81,7 @@ impl CategoryBar {
             button_bar,
             selected_index: selected_index.clone(),
             buttons: buttons.clone(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
             on_category_selected: on_category_selected.clone(),
             on_focus_emoji_grid: on_focus_emoji_grid.clone(),
         };
@@ -131,4 +148,15 @@ impl CategoryBar {
     pub fn set_on_category_selected<F: Fn(usize) + 'static>(&mut self, callback: F) {
         *self.on_category_selected.borrow_mut() = Some(Box::new(callback));
     }
+
+    /// Mark the tab of `category` as the one in view, and no other; `None` marks none.
+    pub fn set_active(&self, category: Option<&str>) {
+        for (button, name) in self.buttons.borrow().iter().zip(&self.categories) {
+            if Some(name.as_str()) == category {
+                button.add_css_class("active-category");
+            } else {
+                button.remove_css_class("active-category");
+            }
+        }
+    }
 }
@@ -1,5 +1,24 @@
 // UI constants for emoji picker
-pub const EMOJI_SIZE: i32 = 32;
+pub const EMOJI_SIZE: i32 = crate::settings::DEFAULT_EMOJI_SIZE;
 pub const SPACING: i32 = 12;
-pub const COLUMNS: i32 = 5;
-pub const ROWS: i32 = 6;
+pub const COLUMNS: i32 = crate::settings::DEFAULT_COLUMNS;
+pub const ROWS: i32 = crate::settings::DEFAULT_ROWS;
+
+// Synthetic "Favorites" page showing the pinned emoji
+pub const FAVORITES_CATEGORY: &str = "Favorites";
+pub const FAVORITES_TAB_EMOJI: &str = "⭐";
+
+// Synthetic "Recent" page showing the usage history
+pub const RECENT_CATEGORY: &str = "Recent";
+pub const RECENT_TAB_EMOJI: &str = "🕘";
+pub const RECENT_CAPACITY: usize = crate::emoji::usage::DEFAULT_CAPACITY;
+
+// Tab of the categories of user snippets (see emoji::collections)
+pub const CUSTOM_TAB_EMOJI: &str = "📝";
+
+// Kaomoji and other text wider than an emoji (see GridLayout::wide)
+pub const KAOMOJI_TAB_EMOJI: &str = "ツ";
+pub const WIDE_SPAN: i32 = 2;
+
+// Global skin tone selector, showing this emoji in each tone
+pub const SKIN_TONE_SELECTOR_EMOJI: &str = "✋";
@@ -0,0 +1,425 @@
+//! All categories in one scrolling grid, each under a section header, for the continuous
+//! browse layout (see [`crate::settings::BrowseLayout`]).
+//!
+//! The grid is a [`ListView`] of the lines laid out by [`Sections`]: a header line per category
+//! followed by rows of its emoji, which start over under a sub-header at each Unicode subgroup.
+//! Only the lines in view have widgets. The header of the section at the top stays in place
+//! over the lines, and follows the scroll position.
+//!
+//! Headers and sub-headers are as high as the rows of emoji. With every line the same height,
+//! the offsets of [`Sections`] match those of the view, also for the lines it has not measured
+//! yet.
+
+use crate::emoji::emoji_data::Emoji;
+use crate::ui::emoji_grid::{mark_selected, show_in_cell, GridCallbacks, SharedSkinTones};
+use crate::ui::layout::GridLayout;
+use crate::ui::sections::{Line, Position, Sections};
+use gtk4::glib::BoxedAnyObject;
+use gtk4::prelude::*;
+use gtk4::{gio, glib, GestureClick, ListItem, ListView, NoSelection, ScrolledWindow, SignalListItemFactory};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Handles to the widgets and state of the continuous grid; clones share them.
+#[derive(Clone)]
+pub struct ContinuousGrid {
+    /// The sticky header over the scrolled lines; this is the widget to show.
+    pub widget: gtk4::Overlay,
+    pub scrolled: ScrolledWindow,
+    pub list_view: ListView,
+    sticky_header: gtk4::Label,
+    /// The categories and their emoji, in their default (untoned) form.
+    sections: Rc<Vec<(&'static str, Vec<Emoji>)>>,
+    /// Layout of each section: wide for kaomoji and snippets.
+    layouts: Rc<Vec<GridLayout>>,
+    geometry: Rc<Sections>,
+    selected: Rc<Cell<Option<Position>>>,
+    /// Section to scroll to once the view knows its height.
+    pending_section: Rc<Cell<Option<usize>>>,
+    /// Section whose header is shown at the top.
+    shown_section: Rc<Cell<Option<usize>>>,
+    /// The lines currently bound to a widget: those in view, and a few around them.
+    bound_items: Rc<RefCell<Vec<ListItem>>>,
+    skin_tones: SharedSkinTones,
+    layout: GridLayout,
+    locales: Rc<Vec<&'static str>>,
+    /// What picking, pinning and selecting an emoji does.
+    pub callbacks: GridCallbacks,
+    on_section_shown: SectionShownCallback,
+}
+
+/// Shared slot for the callback that is notified when another section scrolls to the top.
+pub type SectionShownCallback = Rc<RefCell<Option<Box<dyn Fn(&str) + 'static>>>>;
+
+/// The line held by an item of the list model.
+fn item_line(object: glib::Object) -> Option<Line> {
+    let boxed = object.downcast::<BoxedAnyObject>().ok()?;
+    let line = *boxed.borrow::<Line>();
+    Some(line)
+}
+
+/// The header label and the cell labels of a line widget, as built by the factory.
+fn line_parts(item: &ListItem) -> Option<(gtk4::Label, Vec<gtk4::Label>)> {
+    let row = item.child()?;
+    let header = row.first_child().and_downcast::<gtk4::Label>()?;
+    let cells_box = row.last_child()?;
+    let mut cells = Vec::new();
+    let mut child = cells_box.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        if let Ok(label) = widget.downcast::<gtk4::Label>() {
+            cells.push(label);
+        }
+    }
+    Some((header, cells))
+}
+
+impl ContinuousGrid {
+    /// A grid of `sections` of `(category, emoji)`, sized like a grid of `layout`.
+    pub fn new(
+        sections: Vec<(&'static str, Vec<Emoji>)>,
+        layout: GridLayout,
+        skin_tones: &SharedSkinTones,
+        locales: &Rc<Vec<&'static str>>,
+    ) -> Self {
+        let (grid_width, grid_height) = (layout.grid_width(), layout.grid_height());
+        // Kaomoji and text snippets get wider cells than emoji
+        let layouts: Vec<GridLayout> = sections
+            .iter()
+            .map(|(_, emojis)| if emojis.iter().any(|e| e.is_wide()) { layout.wide() } else { layout })
+            .collect();
+        let sizes: Vec<(Vec<usize>, usize)> = sections
+            .iter()
+            .zip(&layouts)
+            .map(|((_, emojis), l)| (subgroup_sizes(emojis), l.items_per_line() as usize))
+            .collect();
+        let row_height = grid_height / layout.rows;
+        let geometry = Sections::new(&sizes, row_height, row_height);
+
+        let store = gio::ListStore::new::<BoxedAnyObject>();
+        let lines: Vec<BoxedAnyObject> = geometry.lines.iter().map(|&line| BoxedAnyObject::new(line)).collect();
+        store.splice(0, 0, &lines);
+        let list_view = ListView::builder().model(&NoSelection::new(Some(store))).build();
+        list_view.add_css_class("emoji-sections");
+        let scrolled = ScrolledWindow::builder()
+            .child(&list_view)
+            .min_content_height(grid_height)
+            .max_content_height(grid_height)
+            .min_content_width(grid_width)
+            .max_content_width(grid_width)
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .build();
+        scrolled.set_size_request(grid_width, grid_height);
+
+        let sticky_header = section_header(row_height);
+        sticky_header.add_css_class("sticky-section-header");
+        sticky_header.set_valign(gtk4::Align::Start);
+        // Clicks go through to the lines under it
+        sticky_header.set_can_target(false);
+        let widget = gtk4::Overlay::new();
+        widget.set_child(Some(&scrolled));
+        widget.add_overlay(&sticky_header);
+
+        let grid = Self {
+            widget,
+            scrolled,
+            list_view,
+            sticky_header,
+            sections: Rc::new(sections),
+            layouts: Rc::new(layouts),
+            geometry: Rc::new(geometry),
+            selected: Rc::new(Cell::new(None)),
+            pending_section: Rc::new(Cell::new(None)),
+            shown_section: Rc::new(Cell::new(None)),
+            bound_items: Rc::new(RefCell::new(Vec::new())),
+            skin_tones: skin_tones.clone(),
+            layout,
+            locales: locales.clone(),
+            callbacks: GridCallbacks::default(),
+            on_section_shown: Rc::new(RefCell::new(None)),
+        };
+        grid.attach_factory();
+        grid.attach_keyboard_navigation();
+        let grid_clone = grid.clone();
+        grid.scrolled.vadjustment().connect_value_changed(move |_| grid_clone.update_sticky_header());
+        let grid_clone = grid.clone();
+        grid.scrolled.vadjustment().connect_changed(move |_| grid_clone.scroll_to_pending_section());
+        grid.update_sticky_header();
+        grid.selected.set(grid.geometry.first());
+        grid
+    }
+
+    /// Give the view a factory building a header and a row of cells per visible line, and
+    /// showing the one the line needs.
+    fn attach_factory(&self) {
+        let factory = SignalListItemFactory::new();
+        let grid = self.clone();
+        factory.connect_setup(move |_, object| {
+            let Some(item) = object.downcast_ref::<ListItem>() else { return };
+            // Lines are not activated as a whole; the cells are
+            item.set_activatable(false);
+            let row = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+            row.append(&section_header(grid.geometry.line_height(0)));
+            let cells = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
+            for index in 0..grid.layout.columns.max(1) as usize {
+                let item_ref = item.downgrade();
+                let lookup = grid.clone();
+                let label = grid.callbacks.build_cell(&grid.layout, move || {
+                    let position = lookup.cell_position(&item_ref.upgrade()?, index)?;
+                    Some(lookup.emoji_at(position).clone())
+                });
+                // Clicking an emoji also selects it, for the preview and the keys
+                let item_ref = item.downgrade();
+                let select = grid.clone();
+                let click = GestureClick::new();
+                click.connect_pressed(move |_, _, _, _| {
+                    if let Some(position) = item_ref.upgrade().and_then(|item| select.cell_position(&item, index)) {
+                        select.set_selected(position);
+                    }
+                });
+                label.add_controller(click);
+                cells.append(&label);
+            }
+            row.append(&cells);
+            item.set_child(Some(&row));
+        });
+        let grid = self.clone();
+        factory.connect_bind(move |_, object| {
+            if let Some(item) = object.downcast_ref::<ListItem>() {
+                grid.bind_line(item);
+                grid.bound_items.borrow_mut().push(item.clone());
+            }
+        });
+        let bound_items = self.bound_items.clone();
+        factory.connect_unbind(move |_, object| {
+            bound_items.borrow_mut().retain(|item| item.upcast_ref::<glib::Object>() != object);
+        });
+        self.list_view.set_factory(Some(&factory));
+    }
+
+    /// Show the header or the emoji of the line of `item`.
+    fn bind_line(&self, item: &ListItem) {
+        let (Some((header, cells)), Some(line)) = (line_parts(item), item.item().and_then(item_line)) else {
+            return;
+        };
+        let cells_box = cells.first().and_then(|cell| cell.parent());
+        match line {
+            Line::Header { .. } | Line::Subheader { .. } => {
+                if let Line::Subheader { section, start } = line {
+                    header.set_text(&self.emoji_at((section, start)).subgroup.replace('-', " "));
+                    header.add_css_class("section-subheader");
+                } else {
+                    header.set_text(self.sections[line.section()].0);
+                    header.remove_css_class("section-subheader");
+                }
+                header.set_visible(true);
+                if let Some(cells_box) = cells_box {
+                    cells_box.set_visible(false);
+                }
+            }
+            Line::Items { section, start, len } => {
+                header.set_visible(false);
+                if let Some(cells_box) = cells_box {
+                    cells_box.set_visible(true);
+                }
+                let layout = &self.layouts[section];
+                let skin_tones = self.skin_tones.borrow();
+                for (index, label) in cells.iter().enumerate() {
+                    label.set_visible(index < len);
+                    if index >= len {
+                        continue;
+                    }
+                    let emoji = self.emoji_at((section, start + index));
+                    label.set_width_request(layout.item_width());
+                    show_in_cell(label, emoji, skin_tones.apply(emoji), layout, &self.locales);
+                    mark_selected(label, self.selected.get() == Some((section, start + index)));
+                }
+            }
+        }
+    }
+
+    /// The item shown in cell `index` of the line of `item`, if it shows one.
+    fn cell_position(&self, item: &ListItem, index: usize) -> Option<Position> {
+        match item.item().and_then(item_line)? {
+            Line::Items { section, start, len } if index < len => Some((section, start + index)),
+            _ => None,
+        }
+    }
+
+    fn emoji_at(&self, (section, index): Position) -> &Emoji {
+        &self.sections[section].1[index]
+    }
+
+    /// The label showing `position`, unless it is scrolled out of view.
+    fn label_at(&self, position: Position) -> Option<gtk4::Label> {
+        self.bound_items.borrow().iter().find_map(|item| {
+            let (_, cells) = line_parts(item)?;
+            let index = (0..cells.len()).find(|&index| self.cell_position(item, index) == Some(position))?;
+            Some(cells[index].clone())
+        })
+    }
+
+    /// The selected emoji, in its default form.
+    pub fn selected_emoji(&self) -> Option<Emoji> {
+        self.selected.get().map(|position| self.emoji_at(position).clone())
+    }
+
+    /// Select the emoji at `position` and hand it to the selection changed callback.
+    fn set_selected(&self, position: Position) {
+        self.selected.set(Some(position));
+        for item in self.bound_items.borrow().iter() {
+            let Some((_, cells)) = line_parts(item) else { continue };
+            for (index, label) in cells.iter().enumerate() {
+                mark_selected(label, self.cell_position(item, index) == Some(position));
+            }
+        }
+        self.notify_selection();
+    }
+
+    /// Select the emoji at `position`, scrolling its row into view below the sticky header.
+    fn select(&self, position: Position) {
+        self.set_selected(position);
+        let line = self.geometry.line_of(position);
+        let top = self.geometry.line_top(line);
+        let bottom = top + self.geometry.line_height(line);
+        // The row must not end up under the sticky header
+        let header_height = self.sticky_header.height_request();
+        let adjustment = self.scrolled.vadjustment();
+        let value = adjustment.value();
+        if ((top - header_height) as f64) < value {
+            adjustment.set_value((top - header_height) as f64);
+        } else if bottom as f64 > value + adjustment.page_size() {
+            adjustment.set_value(bottom as f64 - adjustment.page_size());
+        }
+    }
+
+    /// Hand the selected emoji to the selection changed callback.
+    pub fn notify_selection(&self) {
+        self.callbacks.notify_selection(self.selected_emoji().as_ref());
+    }
+
+    /// The index of the section of `category`, if there is one.
+    pub fn section_index(&self, category: &str) -> Option<usize> {
+        self.sections.iter().position(|(name, _)| *name == category)
+    }
+
+    /// The category of the section at the top of the view.
+    pub fn section_in_view(&self) -> Option<&'static str> {
+        self.shown_section.get().map(|section| self.sections[section].0)
+    }
+
+    /// Scroll the header of `category` to the top and select its first emoji.
+    pub fn scroll_to_section(&self, category: &str) {
+        let Some(section) = self.section_index(category) else { return };
+        if let Some(position) = self.geometry.first_of(section) {
+            self.set_selected(position);
+        }
+        // A page shown for the first time only gets its height after this
+        self.pending_section.set(Some(section));
+        self.scroll_to_pending_section();
+    }
+
+    /// Scroll to the section of [`ContinuousGrid::scroll_to_section`], once the view is high
+    /// enough to bring it to the top.
+    fn scroll_to_pending_section(&self) {
+        let Some(section) = self.pending_section.get() else { return };
+        let adjustment = self.scrolled.vadjustment();
+        let top = self.geometry.section_top(section) as f64;
+        adjustment.set_value(top);
+        let reachable = adjustment.upper() - adjustment.page_size();
+        if adjustment.page_size() > 0.0 && top.min(reachable) <= adjustment.value() {
+            self.pending_section.set(None);
+        }
+    }
+
+    /// Show the section at the top of the view in the sticky header, and tell the section
+    /// shown callback when it changed.
+    fn update_sticky_header(&self) {
+        let section = self.geometry.section_at(self.scrolled.vadjustment().value());
+        self.sticky_header.set_visible(section.is_some());
+        if section == self.shown_section.get() {
+            return;
+        }
+        self.shown_section.set(section);
+        let Some(section) = section else { return };
+        let category = self.sections[section].0;
+        self.sticky_header.set_text(category);
+        if let Some(ref cb) = *self.on_section_shown.borrow() {
+            cb(category);
+        }
+    }
+
+    /// Show the emoji again with the current skin tone preference, e.g. after it changed.
+    pub fn refresh_skin_tones(&self) {
+        for item in self.bound_items.borrow().iter() {
+            self.bind_line(item);
+        }
+        self.notify_selection();
+    }
+
+    /// Attach the keys to the grid: the arrow keys move the selection across the sections,
+    /// and the selected emoji is copied, toned and pinned as in the paged grids.
+    fn attach_keyboard_navigation(&self) {
+        use gtk4::gdk;
+        let grid = self.clone();
+        let controller = gtk4::EventControllerKey::new();
+        // Ahead of the view, which would move the focus between lines
+        controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+        controller.connect_key_pressed(move |_, keyval, _, state| {
+            let Some(position) = grid.selected.get() else {
+                return gtk4::glib::signal::Propagation::Proceed;
+            };
+            let emoji = grid.emoji_at(position).clone();
+            let callbacks = &grid.callbacks;
+            let geometry = &grid.geometry;
+            match keyval {
+                gdk::Key::Left => grid.select(geometry.previous(position)),
+                gdk::Key::Right => grid.select(geometry.next(position)),
+                gdk::Key::Up => grid.select(geometry.vertical(position, true)),
+                gdk::Key::Down => grid.select(geometry.vertical(position, false)),
+                // Ctrl+D pins the selected emoji to the favorites, or unpins it
+                gdk::Key::d if state.contains(gdk::ModifierType::CONTROL_MASK) => callbacks.toggle_favorite(&emoji),
+                // Shift+Return picks a skin tone for the selected emoji
+                gdk::Key::Return if state.contains(gdk::ModifierType::SHIFT_MASK) => {
+                    if let Some(label) = grid.label_at(position) {
+                        callbacks.show_skin_tone_popover(&label, &emoji, grid.layout.spacing);
+                    }
+                }
+                gdk::Key::Return => {
+                    let text = grid.skin_tones.borrow().apply(&emoji).to_string();
+                    callbacks.copy_emoji(grid.label_at(position).as_ref(), &text);
+                }
+                _ => return gtk4::glib::signal::Propagation::Proceed,
+            }
+            gtk4::glib::signal::Propagation::Stop
+        });
+        self.list_view.add_controller(controller);
+    }
+
+    /// Register a callback to be called with the category whose section scrolls to the top.
+    pub fn set_on_section_shown<F: Fn(&str) + 'static>(&self, callback: F) {
+        *self.on_section_shown.borrow_mut() = Some(Box::new(callback));
+    }
+}
+
+/// Sizes of the runs of `emojis` in the same subgroup, in order.
+fn subgroup_sizes(emojis: &[Emoji]) -> Vec<usize> {
+    let mut sizes: Vec<usize> = Vec::new();
+    for (index, emoji) in emojis.iter().enumerate() {
+        match sizes.last_mut() {
+            Some(size) if emojis[index - 1].subgroup == emoji.subgroup => *size += 1,
+            _ => sizes.push(1),
+        }
+    }
+    sizes
+}
+
+/// A section header label, `height` high.
+fn section_header(height: i32) -> gtk4::Label {
+    let label = gtk4::Label::new(None);
+    label.add_css_class("section-header");
+    label.set_xalign(0.0);
+    label.set_yalign(1.0);
+    label.set_height_request(height);
+    label
+}
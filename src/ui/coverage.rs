@@ -0,0 +1,111 @@
+//! The check behind [`Coverage`]: whether Pango lays out each emoji as a single glyph with the
+//! fonts of the emoji labels. Emoji the fonts lack come out as unknown glyphs (boxes), and
+//! sequences they do not combine come out as several glyphs side by side.
+
+use crate::emoji::coverage::{self, Coverage};
+use crate::trace;
+use crate::ui::emoji_label::EmojiLabel;
+use gtk4::prelude::*;
+use gtk4::{glib, pango};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+/// System-wide fontconfig caches, which are rewritten whenever fonts are installed, updated or
+/// removed.
+const FONTCONFIG_CACHE_DIRS: &[&str] = &["/var/cache/fontconfig", "/usr/lib/fontconfig/cache"];
+
+/// Emoji laid out per idle round of a fresh check, so the window keeps responding meanwhile.
+const BATCH: usize = 100;
+
+/// Find out which emoji the fonts of an emoji label of `emoji_size` points can show, and pass
+/// that to `done`.
+///
+/// Runs from the main loop once it is idle, so it never delays the first frame. A cached
+/// result comes right away; a fresh check is spread over idle rounds and then cached.
+pub fn check_later(emoji_size: i32, done: impl FnOnce(Coverage) + 'static) {
+    glib::idle_add_local_once(move || {
+        // An unparented label has the style of every emoji label: style.css and the user
+        // stylesheet on top of it
+        let label = EmojiLabel::with_size("", emoji_size);
+        let context = label.pango_context();
+        let mut font = context.font_description().unwrap_or_default();
+        font.set_size(emoji_size * pango::SCALE);
+        let configuration = font_configuration(&context, &font);
+        let cache_dir = coverage::default_dir();
+        if let Some(cached) = Coverage::cached(&cache_dir, &configuration) {
+            return finish(cached, done);
+        }
+        let mut texts = coverage::texts().peekable();
+        let mut unsupported = Vec::new();
+        let mut done = Some(done);
+        glib::idle_add_local(move || {
+            for text in texts.by_ref().take(BATCH) {
+                if !renders_as_one_glyph(&context, &font, text) {
+                    unsupported.push(text);
+                }
+            }
+            if texts.peek().is_some() {
+                return glib::ControlFlow::Continue;
+            }
+            let checked = Coverage::from_unsupported(unsupported.drain(..));
+            checked.store(&cache_dir, &configuration);
+            if let Some(done) = done.take() {
+                finish(checked, done);
+            }
+            glib::ControlFlow::Break
+        });
+    });
+}
+
+/// Pass the checked or cached `coverage` to `done`, marking it in the startup trace and logging
+/// how many emoji it hides.
+fn finish(coverage: Coverage, done: impl FnOnce(Coverage)) {
+    trace::mark("font coverage checked");
+    if coverage.unsupported_count() > 0 {
+        log::info!("Hiding {} emoji and skin tones the fonts cannot show", coverage.unsupported_count());
+    }
+    done(coverage);
+}
+
+/// Whether `text` is laid out as one glyph, none of them unknown, in `font`.
+fn renders_as_one_glyph(context: &pango::Context, font: &pango::FontDescription, text: &str) -> bool {
+    let layout = pango::Layout::new(context);
+    layout.set_font_description(Some(font));
+    layout.set_text(text);
+    if layout.unknown_glyphs_count() > 0 {
+        return false;
+    }
+    let mut glyphs = 0;
+    let mut iter = layout.iter();
+    loop {
+        if let Some(run) = iter.run() {
+            // Joiners and variation selectors that are shaped away leave empty glyphs behind
+            glyphs += run.glyph_string().glyph_info().iter().filter(|info| info.glyph() != pango::GLYPH_EMPTY).count();
+        }
+        if !iter.next_run() {
+            break;
+        }
+    }
+    glyphs == 1
+}
+
+/// A description of the fonts that changes when they do: `font` itself, the installed font
+/// families, and when fontconfig last updated its caches.
+fn font_configuration(context: &pango::Context, font: &pango::FontDescription) -> String {
+    let mut families: Vec<String> = context
+        .font_map()
+        .map(|map| map.list_families().iter().map(|family| family.name().to_string()).collect())
+        .unwrap_or_default();
+    families.sort();
+    let mut configuration = format!("{}\n{}", font, families.join("\n"));
+    let user_cache = glib::user_cache_dir().join("fontconfig");
+    for dir in FONTCONFIG_CACHE_DIRS.iter().map(PathBuf::from).chain([user_cache]) {
+        let modified = fs::metadata(&dir).and_then(|metadata| metadata.modified());
+        if let Ok(since_epoch) = modified.map(|time| time.duration_since(UNIX_EPOCH).unwrap_or_default()) {
+            let _ = write!(configuration, "\n{} {}", dir.display(), since_epoch.as_secs());
+        }
+    }
+    configuration
+}
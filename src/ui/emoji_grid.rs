@@ -1,106 +1,351 @@
 use crate::clipboard;
 use crate::ui::emoji_label::EmojiLabel;
+use crate::ui::layout::GridLayout;
+use gtk4::glib::BoxedAnyObject;
 use gtk4::prelude::*;
-use gtk4::{FlowBox, ScrolledWindow, GestureClick};
-use crate::ui::constants::{SPACING, COLUMNS};
+use gtk4::{gio, glib, GestureClick, GestureLongPress, GridView, ListItem, ListScrollFlags, Popover, ScrolledWindow};
+use gtk4::{SignalListItemFactory, SingleSelection};
 use std::rc::Rc;
 use std::cell::RefCell;
 use crate::emoji::emoji_data::Emoji;
+use crate::emoji::skin_tone::{SkinTone, SkinTonePreference};
 
+/// Handles to the widgets and state of one grid; clones share them.
+///
+/// The grid is a [`GridView`] over a list model of the emoji, so only the cells in view have
+/// widgets: a category of hundreds of emoji costs a few dozen labels, which are reused while
+/// scrolling.
+#[derive(Clone)]
 pub struct EmojiGrid {
     pub scrolled: ScrolledWindow,
-    pub flowbox: FlowBox,
-    pub selected_index: Rc<RefCell<Option<usize>>>,
-    pub emoji_labels: Rc<RefCell<Vec<gtk4::Label>>>,
+    pub grid_view: GridView,
+    /// The emoji shown, in their default (untoned) form, each in a [`BoxedAnyObject`].
+    pub store: gio::ListStore,
+    pub selection: SingleSelection,
+    /// The cells currently showing an emoji: those in view, and a few around them.
+    pub bound_items: Rc<RefCell<Vec<ListItem>>>,
+    /// Skin tone preference applied when rendering and copying, shared between grids.
+    pub skin_tones: SharedSkinTones,
+    pub layout: GridLayout,
+    /// Locales of the emoji names shown on hover, best first.
+    pub locales: Rc<Vec<&'static str>>,
     pub on_emoji_selected: Rc<RefCell<Option<Box<dyn Fn(usize) + 'static>>>>,
+    /// What picking, pinning and selecting an emoji does.
+    pub callbacks: GridCallbacks,
 }
 
-impl EmojiGrid {
-    /// Update the emojis displayed in the grid, clearing and rebuilding the FlowBox and label state.
-    pub fn update_emojis(&mut self, emojis: &[&Emoji], grid_width: i32, grid_height: i32) {
-        // Remove all children from the flowbox
-        while let Some(child) = self.flowbox.first_child() {
-            self.flowbox.remove(&child);
+/// The callbacks of a grid, in slots shared by its clones and its cells.
+#[derive(Clone, Default)]
+pub struct GridCallbacks {
+    /// Called with the emoji text whenever an emoji is picked (click or Return). When set, it
+    /// delivers the emoji (clipboard or insertion); otherwise the grid copies it itself.
+    pub on_emoji_copied: EmojiCopiedCallback,
+    /// Called with the default form of an emoji and the tone picked for it in its popover.
+    pub on_skin_tone_chosen: SkinToneChosenCallback,
+    /// Tells whether the fonts can show an emoji, so the popover leaves out tones they cannot.
+    pub is_supported: SupportQuery,
+    /// Tells whether the default form of an emoji is a favorite, for its context menu.
+    pub is_favorite: FavoriteQuery,
+    /// Called with the default form of an emoji to pin it to the favorites or unpin it.
+    pub on_favorite_toggled: FavoriteToggledCallback,
+    /// Called with a favorite and the places to move it by. Only set on the Favorites page,
+    /// where Ctrl+arrows reorder.
+    pub on_favorite_moved: FavoriteMovedCallback,
+    /// Called with the selected emoji (in its default form) whenever the selection changes.
+    pub on_selection_changed: SelectionChangedCallback,
+}
+
+/// Shared slot for the callback that is notified when an emoji is copied.
+pub type EmojiCopiedCallback = Rc<RefCell<Option<Box<dyn Fn(&str) + 'static>>>>;
+
+/// Shared slot for the callback that is notified when a tone is picked for a single emoji.
+pub type SkinToneChosenCallback = Rc<RefCell<Option<Box<dyn Fn(&str, SkinTone) + 'static>>>>;
+
+/// Shared slot for the query whether the fonts can show an emoji.
+pub type SupportQuery = Rc<RefCell<Option<Box<dyn Fn(&str) -> bool + 'static>>>>;
+
+/// Shared slot for the query whether an emoji is a favorite.
+pub type FavoriteQuery = Rc<RefCell<Option<Box<dyn Fn(&str) -> bool + 'static>>>>;
+
+/// Shared slot for the callback that pins or unpins an emoji.
+pub type FavoriteToggledCallback = Rc<RefCell<Option<Box<dyn Fn(&str) + 'static>>>>;
+
+/// Shared slot for the callback that moves a favorite.
+pub type FavoriteMovedCallback = Rc<RefCell<Option<Box<dyn Fn(&str, isize) + 'static>>>>;
+
+/// Shared slot for the callback that is notified when the selection moves.
+pub type SelectionChangedCallback = Rc<RefCell<Option<Box<dyn Fn(Option<&Emoji>) + 'static>>>>;
+
+/// Skin tone preference shared by all grids of a window.
+pub type SharedSkinTones = Rc<RefCell<SkinTonePreference>>;
+
+/// The emoji held by an item of [`EmojiGrid::store`].
+fn item_emoji(object: glib::Object) -> Option<Emoji> {
+    let boxed = object.downcast::<BoxedAnyObject>().ok()?;
+    let emoji = boxed.borrow::<Emoji>().clone();
+    Some(emoji)
+}
+
+/// The label of a cell, as built by [`GridCallbacks::build_cell`].
+fn cell_label(item: &ListItem) -> Option<gtk4::Label> {
+    item.child().and_downcast::<gtk4::Label>()
+}
+
+/// Mark `label` as the selected emoji, or unmark it.
+pub fn mark_selected(label: &gtk4::Label, selected: bool) {
+    if selected {
+        label.add_css_class("selected-emoji");
+    } else {
+        label.remove_css_class("selected-emoji");
+    }
+}
+
+/// Show `emoji` as `shown` (e.g. in a skin tone) in a cell label of `layout`, with its name
+/// in the first of `locales` on hover.
+pub fn show_in_cell(label: &gtk4::Label, emoji: &Emoji, shown: &str, layout: &GridLayout, locales: &[&str]) {
+    // Kaomoji and snippets get a smaller text font, also when they are mixed with emoji
+    let wide = emoji.is_wide();
+    EmojiLabel::set_font_size(label, if wide { layout.text_size() } else { layout.emoji_size });
+    EmojiLabel::set_text_style(label, wide);
+    label.set_text(shown);
+    // Kaomoji and snippets may be cut short in their cell; show them in full on hover
+    if wide {
+        label.set_tooltip_text(Some(&format!("{}\n{}", emoji.ch, emoji.name(locales))));
+    } else {
+        label.set_tooltip_text(Some(emoji.name(locales)));
+    }
+}
+
+impl GridCallbacks {
+    /// Build the label of a cell of `layout`, copying its text on click, offering to pin its
+    /// emoji on right click and offering its skin tones on long press. A cell shows other
+    /// emoji over time, so the gestures ask `emoji` which one it shows when they fire.
+    pub fn build_cell(&self, layout: &GridLayout, emoji: impl Fn() -> Option<Emoji> + 'static) -> gtk4::Label {
+        let label = EmojiLabel::with_size("", layout.emoji_size);
+        label.set_widget_name("emoji");
+        label.set_halign(gtk4::Align::Fill);
+        label.set_valign(gtk4::Align::Start);
+        label.set_width_request(layout.item_width());
+        label.set_height_request(layout.grid_height() / layout.rows);
+        // Copy to clipboard and visual feedback on click. Copy on release, so a long press
+        // can claim the click for the skin tone popover instead.
+        let label_clone = label.clone();
+        let callbacks = self.clone();
+        let gesture = GestureClick::new();
+        gesture.connect_released(move |_, _, _, _| {
+            let text = label_clone.text().to_string();
+            callbacks.copy_emoji(Some(&label_clone), &text);
+        });
+        label.add_controller(gesture);
+        let emoji = Rc::new(emoji);
+        // Right click offers pinning the emoji to the favorites
+        let secondary = GestureClick::builder().button(gtk4::gdk::BUTTON_SECONDARY).build();
+        let callbacks = self.clone();
+        let label_clone = label.clone();
+        let emoji_clone = emoji.clone();
+        secondary.connect_pressed(move |_, _, _, _| {
+            if let Some(emoji) = emoji_clone() {
+                callbacks.show_context_menu(&label_clone, &emoji);
+            }
+        });
+        label.add_controller(secondary);
+        let callbacks = self.clone();
+        let label_clone = label.clone();
+        let spacing = layout.spacing;
+        let long_press = GestureLongPress::new();
+        long_press.connect_pressed(move |gesture, _, _| {
+            if emoji().is_some_and(|emoji| callbacks.show_skin_tone_popover(&label_clone, &emoji, spacing)) {
+                // Cancels the click gesture, so the release does not copy the emoji too
+                gesture.set_state(gtk4::EventSequenceState::Claimed);
+            }
+        });
+        label.add_controller(long_press);
+        label
+    }
+
+    /// Pop up the skin tones of `emoji` under its label, `spacing` apart. Picking one copies
+    /// it and remembers the tone for this emoji. Tones the fonts cannot show are left out.
+    /// Returns whether there was anything to show.
+    pub fn show_skin_tone_popover(&self, label: &gtk4::Label, emoji: &Emoji, spacing: i32) -> bool {
+        let mut choices = emoji.skin_tone_choices();
+        if let Some(ref is_supported) = *self.is_supported.borrow() {
+            choices.retain(|&(_, text)| is_supported(text));
+        }
+        if choices.len() < 2 {
+            return false;
         }
-        self.emoji_labels.borrow_mut().clear();
-        // Add new emoji labels
-        for emoji in emojis {
-            let label = EmojiLabel::new(emoji.ch);
-            label.set_widget_name("emoji");
-            label.add_css_class("emoji-label");
-            label.set_halign(gtk4::Align::Fill);
-            label.set_valign(gtk4::Align::Start);
-            label.set_width_request(grid_width / COLUMNS);
-            label.set_height_request(grid_height / crate::ui::constants::ROWS);
-            // Copy to clipboard and visual feedback on click
-            let emoji_str = emoji.ch.to_string();
-            let label_clone = label.clone();
-            let gesture = GestureClick::new();
-            gesture.connect_pressed(move |_, _, _, _| {
-                clipboard::copy(&emoji_str);
-                label_clone.add_css_class("copied-emoji");
-                let label_inner = label_clone.clone();
-                gtk4::glib::timeout_add_local_once(std::time::Duration::from_millis(500), move || {
-                    label_inner.remove_css_class("copied-emoji");
-                });
+        let popover = Popover::new();
+        popover.set_parent(label);
+        popover.add_css_class("skin-tone-popover");
+        let row = gtk4::Box::new(gtk4::Orientation::Horizontal, spacing / 3);
+        for (tone, text) in choices {
+            let button = gtk4::Button::with_label(text);
+            button.add_css_class("flat");
+            button.add_css_class("skin-tone-choice");
+            button.set_tooltip_text(Some(tone.label()));
+            let popover = popover.clone();
+            let label = label.clone();
+            let base = emoji.ch;
+            let callbacks = self.clone();
+            button.connect_clicked(move |_| {
+                popover.popdown();
+                if let Some(ref cb) = *callbacks.on_skin_tone_chosen.borrow() {
+                    cb(base, tone);
+                }
+                label.set_text(text);
+                callbacks.copy_emoji(Some(&label), text);
             });
-            label.add_controller(gesture);
-            self.flowbox.insert(&label, -1);
-            self.emoji_labels.borrow_mut().push(label);
+            row.append(&button);
+        }
+        popover.set_child(Some(&row));
+        // Drop the popover once closed; unparenting inside the signal handler is not allowed
+        popover.connect_closed(|popover| {
+            let popover = popover.clone();
+            gtk4::glib::idle_add_local_once(move || popover.unparent());
+        });
+        popover.popup();
+        if let Some(first) = row.first_child() {
+            first.grab_focus();
+        }
+        true
+    }
+
+    /// Pop up the actions for `emoji` under its label: pin it to the favorites or unpin it.
+    pub fn show_context_menu(&self, label: &gtk4::Label, emoji: &Emoji) {
+        let pinned = match *self.is_favorite.borrow() {
+            Some(ref is_favorite) => is_favorite(emoji.ch),
+            None => return,
+        };
+        let popover = Popover::new();
+        popover.set_parent(label);
+        let button = gtk4::Button::with_label(if pinned { "Unpin from Favorites" } else { "Pin to Favorites" });
+        button.add_css_class("flat");
+        let callbacks = self.clone();
+        let emoji = emoji.clone();
+        let popover_clone = popover.clone();
+        button.connect_clicked(move |_| {
+            popover_clone.popdown();
+            callbacks.toggle_favorite(&emoji);
+        });
+        popover.set_child(Some(&button));
+        popover.connect_closed(|popover| {
+            let popover = popover.clone();
+            gtk4::glib::idle_add_local_once(move || popover.unparent());
+        });
+        popover.popup();
+        button.grab_focus();
+    }
+
+    /// Hand `emoji` to the favorite toggled callback.
+    pub fn toggle_favorite(&self, emoji: &Emoji) {
+        if let Some(ref cb) = *self.on_favorite_toggled.borrow() {
+            cb(emoji.ch);
+        }
+    }
+
+    /// Flash the label, if it is in view, and hand `emoji` to the copied callback, or copy it
+    /// to the clipboard if there is none.
+    pub fn copy_emoji(&self, label: Option<&gtk4::Label>, emoji: &str) {
+        if let Some(label) = label {
+            label.add_css_class("copied-emoji");
+            let label_inner = label.clone();
+            gtk4::glib::timeout_add_local_once(std::time::Duration::from_millis(500), move || {
+                label_inner.remove_css_class("copied-emoji");
+            });
+        }
+        match *self.on_emoji_copied.borrow() {
+            Some(ref cb) => cb(emoji),
+            None => clipboard::copy(emoji),
+        }
+    }
+
+    /// Hand the selected emoji to the selection changed callback.
+    pub fn notify_selection(&self, emoji: Option<&Emoji>) {
+        if let Some(ref cb) = *self.on_selection_changed.borrow() {
+            cb(emoji);
+        }
+    }
+
+    /// Register a callback to be called with the emoji text whenever an emoji is copied.
+    pub fn set_on_emoji_copied<F: Fn(&str) + 'static>(&self, callback: F) {
+        *self.on_emoji_copied.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback to be called when a skin tone is picked for a single emoji.
+    pub fn set_on_skin_tone_chosen<F: Fn(&str, SkinTone) + 'static>(&self, callback: F) {
+        *self.on_skin_tone_chosen.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback to be called with the selected emoji whenever the selection moves.
+    pub fn set_on_selection_changed<F: Fn(Option<&Emoji>) + 'static>(&self, callback: F) {
+        *self.on_selection_changed.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register the query whether the fonts can show an emoji; without it every skin tone is
+    /// offered.
+    pub fn set_is_supported<F: Fn(&str) -> bool + 'static>(&self, query: F) {
+        *self.is_supported.borrow_mut() = Some(Box::new(query));
+    }
+
+    /// Register the query whether an emoji is a favorite; without it there is no context menu.
+    pub fn set_is_favorite<F: Fn(&str) -> bool + 'static>(&self, query: F) {
+        *self.is_favorite.borrow_mut() = Some(Box::new(query));
+    }
+
+    /// Register a callback to be called when an emoji is pinned or unpinned.
+    pub fn set_on_favorite_toggled<F: Fn(&str) + 'static>(&self, callback: F) {
+        *self.on_favorite_toggled.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback to be called when a favorite is moved, enabling Ctrl+arrows.
+    pub fn set_on_favorite_moved<F: Fn(&str, isize) + 'static>(&self, callback: F) {
+        *self.on_favorite_moved.borrow_mut() = Some(Box::new(callback));
+    }
+}
+
+impl EmojiGrid {
+    /// Update the emojis displayed in the grid, selecting the first one.
+    pub fn update_emojis(&mut self, emojis: &[&Emoji]) {
+        let items: Vec<BoxedAnyObject> = emojis.iter().map(|&e| BoxedAnyObject::new(e.clone())).collect();
+        // A single change for the whole list; the view then binds only the cells in sight
+        self.store.splice(0, self.store.n_items(), &items);
+        if !items.is_empty() {
+            self.selection.set_selected(0);
+            self.grid_view.scroll_to(0, ListScrollFlags::NONE, None);
         }
-        // Reset selection
-        if !self.emoji_labels.borrow().is_empty() {
-            *self.selected_index.borrow_mut() = Some(0);
-            if let Some(label) = self.emoji_labels.borrow().get(0) {
-                label.add_css_class("selected-emoji");
+        self.notify_selection();
+    }
+
+    /// Show the emoji again with the current skin tone preference, e.g. after it changed.
+    pub fn refresh_skin_tones(&self) {
+        {
+            let skin_tones = self.skin_tones.borrow();
+            for item in self.bound_items.borrow().iter() {
+                if let (Some(label), Some(emoji)) = (cell_label(item), item.item().and_then(item_emoji)) {
+                    label.set_text(skin_tones.apply(&emoji));
+                }
             }
-        } else {
-            *self.selected_index.borrow_mut() = None;
         }
-        self.attach_keyboard_navigation();
+        self.notify_selection();
     }
 
-    pub fn new(emojis: &[&Emoji], grid_width: i32, grid_height: i32) -> Self {
-        let flowbox = FlowBox::builder()
-            .row_spacing(SPACING as u32)
-            .column_spacing(SPACING as u32)
+    pub fn new(emojis: &[&Emoji], layout: GridLayout, skin_tones: &SharedSkinTones, locales: &Rc<Vec<&'static str>>) -> Self {
+        let (grid_width, grid_height) = (layout.grid_width(), layout.grid_height());
+        let store = gio::ListStore::new::<BoxedAnyObject>();
+        let selection = SingleSelection::new(Some(store.clone()));
+        let grid_view = GridView::builder()
+            .model(&selection)
+            .min_columns(layout.items_per_line() as u32)
+            .max_columns(layout.items_per_line() as u32)
             .margin_top(0)
             .margin_bottom(0)
             .margin_start(0)
             .margin_end(0)
-            .min_children_per_line(COLUMNS as u32)
-            .max_children_per_line(COLUMNS as u32)
-            .selection_mode(gtk4::SelectionMode::None)
-            .halign(gtk4::Align::Start)
-            .valign(gtk4::Align::Start)
             .build();
-        let emoji_labels = Rc::new(RefCell::new(Vec::new()));
-        for emoji in emojis {
-            let label = EmojiLabel::new(emoji.ch);
-            label.set_widget_name("emoji");
-            label.add_css_class("emoji-label");
-            label.set_halign(gtk4::Align::Fill);
-            label.set_valign(gtk4::Align::Start);
-            label.set_width_request(grid_width / COLUMNS);
-            label.set_height_request(grid_height / crate::ui::constants::ROWS);
-            // Copy to clipboard and visual feedback on click
-            let emoji_str = emoji.ch.to_string();
-            let label_clone = label.clone();
-            let gesture = GestureClick::new();
-            gesture.connect_pressed(move |_, _, _, _| {
-                clipboard::copy(&emoji_str);
-                label_clone.add_css_class("copied-emoji");
-                let label_inner = label_clone.clone();
-                gtk4::glib::timeout_add_local_once(std::time::Duration::from_millis(500), move || {
-                    label_inner.remove_css_class("copied-emoji");
-                });
-            });
-            label.add_controller(gesture);
-            flowbox.insert(&label, -1);
-            emoji_labels.borrow_mut().push(label);
-        }
+        grid_view.add_css_class("emoji-grid");
+        // The view must be the direct child of the scrolled window to only realize visible cells
         let scrolled = ScrolledWindow::builder()
-            .child(&flowbox)
+            .child(&grid_view)
             .min_content_height(grid_height)
             .max_content_height(grid_height)
             .min_content_width(grid_width)
@@ -113,108 +358,162 @@ impl EmojiGrid {
             .build();
         scrolled.set_size_request(grid_width, grid_height);
 
-        let selected_index = Rc::new(RefCell::new(None));
-        // Select the first emoji by default if any exist
-        if !emoji_labels.borrow().is_empty() {
-            *selected_index.borrow_mut() = Some(0);
-            // Add visual selection
-            if let Some(label) = emoji_labels.borrow().get(0) {
-                label.add_css_class("selected-emoji");
-            }
-        }
-        let on_emoji_selected = Rc::new(RefCell::new(None));
-        Self {
+        let mut grid = Self {
             scrolled,
-            flowbox,
-            selected_index: selected_index.clone(),
-            emoji_labels: emoji_labels.clone(),
-            on_emoji_selected: on_emoji_selected.clone(),
-        }
+            grid_view,
+            store,
+            selection,
+            bound_items: Rc::new(RefCell::new(Vec::new())),
+            skin_tones: skin_tones.clone(),
+            layout,
+            locales: locales.clone(),
+            on_emoji_selected: Rc::new(RefCell::new(None)),
+            callbacks: GridCallbacks::default(),
+        };
+        grid.attach_factory();
+        grid.attach_keyboard_navigation();
+        let grid_clone = grid.clone();
+        grid.selection.connect_selected_notify(move |_| {
+            grid_clone.mark_selection();
+            grid_clone.notify_selection();
+        });
+        grid.update_emojis(emojis);
+        grid
     }
 
-    /// Attach keyboard navigation to the grid. This will always use the current emoji list and selection.
-    pub fn attach_keyboard_navigation(&self) {
-        // Remove all previous key controllers to avoid stacking
-        let model = self.flowbox.observe_controllers();
-        let mut to_remove = Vec::new();
-        for i in 0..model.n_items() {
-            if let Some(obj) = model.item(i) {
-                if let Ok(controller) = obj.clone().downcast::<gtk4::EventControllerKey>() {
-                    let ec: gtk4::EventController = controller.upcast();
-                    to_remove.push(ec);
-                }
+    /// Number of emoji in the grid.
+    pub fn len(&self) -> usize {
+        self.store.n_items() as usize
+    }
+
+    /// Whether the grid has no emoji.
+    pub fn is_empty(&self) -> bool {
+        self.store.n_items() == 0
+    }
+
+    /// Give the view a factory building a label per visible cell and showing an emoji in it.
+    fn attach_factory(&self) {
+        let factory = SignalListItemFactory::new();
+        let callbacks = self.callbacks.clone();
+        let layout = self.layout;
+        factory.connect_setup(move |_, object| {
+            if let Some(item) = object.downcast_ref::<ListItem>() {
+                let item_ref = item.downgrade();
+                let label = callbacks.build_cell(&layout, move || item_ref.upgrade()?.item().and_then(item_emoji));
+                item.set_child(Some(&label));
+            }
+        });
+        let grid = self.clone();
+        factory.connect_bind(move |_, object| {
+            if let Some(item) = object.downcast_ref::<ListItem>() {
+                grid.bind_cell(item);
+                grid.bound_items.borrow_mut().push(item.clone());
             }
+        });
+        let bound_items = self.bound_items.clone();
+        factory.connect_unbind(move |_, object| {
+            bound_items.borrow_mut().retain(|item| item.upcast_ref::<glib::Object>() != object);
+        });
+        self.grid_view.set_factory(Some(&factory));
+    }
+
+    /// Show the emoji of `item` in its cell.
+    fn bind_cell(&self, item: &ListItem) {
+        let (Some(label), Some(emoji)) = (cell_label(item), item.item().and_then(item_emoji)) else {
+            return;
+        };
+        let shown = self.skin_tones.borrow().apply(&emoji).to_string();
+        show_in_cell(&label, &emoji, &shown, &self.layout, &self.locales);
+        mark_selected(&label, item.position() == self.selection.selected());
+    }
+
+    /// Select the emoji at `index`, if there is one, scrolling it into view. The keyboard
+    /// focus moves along when it is in the grid.
+    pub fn select(&self, index: usize) {
+        if index < self.len() {
+            let flags = ListScrollFlags::SELECT | ListScrollFlags::FOCUS;
+            self.grid_view.scroll_to(index as u32, flags, None);
         }
-        for c in to_remove {
-            self.flowbox.remove_controller(&c);
+    }
+
+    /// The selected emoji, in its default form.
+    pub fn selected_emoji(&self) -> Option<Emoji> {
+        self.selection.selected_item().and_then(item_emoji)
+    }
+
+    /// The label showing the selected emoji, unless it is scrolled out of view.
+    fn selected_label(&self) -> Option<gtk4::Label> {
+        let selected = self.selection.selected();
+        self.bound_items.borrow().iter().find(|item| item.position() == selected).and_then(cell_label)
+    }
+
+    /// Move the selection highlight to the label of the selected emoji.
+    fn mark_selection(&self) {
+        let selected = self.selection.selected();
+        for item in self.bound_items.borrow().iter() {
+            if let Some(label) = cell_label(item) {
+                mark_selected(&label, item.position() == selected);
+            }
         }
+    }
+
+    /// Hand the selected emoji to the selection changed callback.
+    pub fn notify_selection(&self) {
+        self.callbacks.notify_selection(self.selected_emoji().as_ref());
+    }
 
+    /// Attach the keys for the selected emoji to the grid. The view itself moves the selection
+    /// with the arrow keys.
+    fn attach_keyboard_navigation(&self) {
         use gtk4::gdk;
-        let selected_index_clone = self.selected_index.clone();
-        let emoji_labels_clone = self.emoji_labels.clone();
         let on_emoji_selected_cb = self.on_emoji_selected.clone();
-        let focus_category_bar: Rc<RefCell<Option<Box<dyn Fn()>>>> = Rc::new(RefCell::new(None));
+        let grid = self.clone();
+        let columns = self.layout.items_per_line() as isize;
         let controller = gtk4::EventControllerKey::new();
-        controller.connect_key_pressed(move |_, keyval, _, _| {
-            let total_emojis = emoji_labels_clone.borrow().len();
-            let mut selected = selected_index_clone.borrow().unwrap_or(0);
-            if total_emojis == 0 {
+        // Ahead of the view, whose cells would take Return and whose Ctrl+arrows move the focus
+        controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+        controller.connect_key_pressed(move |_, keyval, _, state| {
+            let Some(emoji) = grid.selected_emoji() else {
                 return gtk4::glib::signal::Propagation::Proceed;
-            }
+            };
+            let selected = grid.selection.selected() as usize;
+            let callbacks = &grid.callbacks;
+            let ctrl = state.contains(gdk::ModifierType::CONTROL_MASK);
             match keyval {
-                gdk::Key::Right => {
-                    if selected + 1 < total_emojis {
-                        selected += 1;
+                // Ctrl+D pins the selected emoji to the favorites, or unpins it
+                gdk::Key::d if ctrl => callbacks.toggle_favorite(&emoji),
+                // On the Favorites page, Ctrl+arrows move the selected favorite
+                gdk::Key::Left | gdk::Key::Right | gdk::Key::Up | gdk::Key::Down
+                    if ctrl && callbacks.on_favorite_moved.borrow().is_some() =>
+                {
+                    let offset = match keyval {
+                        gdk::Key::Left => -1,
+                        gdk::Key::Right => 1,
+                        gdk::Key::Up => -columns,
+                        _ => columns,
+                    };
+                    if let Some(ref cb) = *callbacks.on_favorite_moved.borrow() {
+                        cb(emoji.ch, offset);
                     }
                 }
-                gdk::Key::Left => {
-                    if selected > 0 {
-                        selected -= 1;
-                    }
-                }
-                gdk::Key::Down => {
-                    if selected + (COLUMNS as usize) < total_emojis {
-                        selected += COLUMNS as usize;
-                    }
-                }
-                gdk::Key::Up => {
-                    if selected >= COLUMNS as usize {
-                        selected -= COLUMNS as usize;
+                // Shift+Return picks a skin tone for the selected emoji
+                gdk::Key::Return if state.contains(gdk::ModifierType::SHIFT_MASK) => {
+                    if let Some(label) = grid.selected_label() {
+                        callbacks.show_skin_tone_popover(&label, &emoji, grid.layout.spacing);
                     }
                 }
                 gdk::Key::Return => {
-                    if let Some(label) = emoji_labels_clone.borrow().get(selected) {
-                        let emoji = label.text().to_string();
-                        clipboard::copy(&emoji);
-                        label.add_css_class("copied-emoji");
-                        let label_clone = label.clone();
-                        gtk4::glib::timeout_add_local_once(std::time::Duration::from_millis(500), move || {
-                            label_clone.remove_css_class("copied-emoji");
-                        });
-                    }
+                    let text = grid.skin_tones.borrow().apply(&emoji).to_string();
+                    callbacks.copy_emoji(grid.selected_label().as_ref(), &text);
                     if let Some(ref cb) = *on_emoji_selected_cb.borrow() {
                         cb(selected);
                     }
                 }
-                gdk::Key::Tab if keyval == gdk::Key::ISO_Left_Tab => {
-                    if let Some(ref cb) = &*focus_category_bar.borrow() {
-                        cb();
-                        return gtk4::glib::signal::Propagation::Stop;
-                    }
-                }
-                _ => {}
-            }
-            *selected_index_clone.borrow_mut() = Some(selected);
-            for (i, label) in emoji_labels_clone.borrow().iter().enumerate() {
-                if i == selected {
-                    label.add_css_class("selected-emoji");
-                } else {
-                    label.remove_css_class("selected-emoji");
-                }
+                _ => return gtk4::glib::signal::Propagation::Proceed,
             }
-            gtk4::glib::signal::Propagation::Proceed
+            gtk4::glib::signal::Propagation::Stop
         });
-        self.flowbox.add_controller(controller);
+        self.grid_view.add_controller(controller);
     }
 
     /// Register a callback to be called when an emoji is selected (e.g. via Return key).
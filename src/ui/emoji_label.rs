@@ -1,5 +1,6 @@
 use gtk4::prelude::*;
 use gtk4::{Label, Align};
+use gtk4::pango::EllipsizeMode;
 use gtk4::pango;
 use crate::ui::constants::EMOJI_SIZE;
 
@@ -9,19 +10,48 @@ pub struct EmojiLabel;
 impl EmojiLabel {
     /// Create a new emoji label with consistent styling and sizing.
     pub fn new(text: &str) -> Label {
+        Self::with_size(text, EMOJI_SIZE)
+    }
+
+    /// Create a new emoji label of `size` points.
+    pub fn with_size(text: &str, size: i32) -> Label {
         let label = Label::new(Some(text));
         label.add_css_class("emoji-label");
         label.set_halign(Align::Center);
         label.set_valign(Align::Center);
-        label.set_width_request(EMOJI_SIZE);
-        label.set_height_request(EMOJI_SIZE);
+        label.set_width_request(size);
+        label.set_height_request(size);
+        Self::set_font_size(&label, size);
+        label
+    }
 
+    /// Create a label for text wider than an emoji, such as a kaomoji: `size` points, on one
+    /// line, ellipsized when it does not fit the width it is given.
+    pub fn text(text: &str, size: i32) -> Label {
+        let label = Self::with_size(text, size);
+        Self::set_text_style(&label, true);
+        label
+    }
+
+    /// Set the font size of `label` to `size` points.
+    pub fn set_font_size(label: &Label, size: i32) {
         // Set font size using Pango (in points * PANGO_SCALE)
         let attr_list = pango::AttrList::new();
-        let font_size_attr = pango::AttrSize::new((EMOJI_SIZE * pango::SCALE) as i32);
+        let font_size_attr = pango::AttrSize::new(size * pango::SCALE);
         attr_list.insert(font_size_attr.upcast());
         label.set_attributes(Some(&attr_list));
+    }
 
-        label
+    /// Switch `label` between showing an emoji and showing wider text (see [`EmojiLabel::text`]),
+    /// for labels that are reused for other items.
+    pub fn set_text_style(label: &Label, text: bool) {
+        if text {
+            label.add_css_class("emoji-text");
+            label.set_ellipsize(EllipsizeMode::End);
+        } else {
+            label.remove_css_class("emoji-text");
+            label.set_ellipsize(EllipsizeMode::None);
+        }
+        label.set_single_line_mode(text);
     }
 }
@@ -0,0 +1,84 @@
+//! Sizes of the emoji grids, as configured in the settings.
+
+use crate::settings::Settings;
+use crate::ui::constants::{SPACING, WIDE_SPAN};
+
+/// How many emoji a grid shows and how large they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridLayout {
+    pub columns: i32,
+    /// Rows visible without scrolling.
+    pub rows: i32,
+    /// Emoji size in points.
+    pub emoji_size: i32,
+    pub spacing: i32,
+    /// Emoji cells each item takes; more than one for wide text such as kaomoji.
+    pub span: i32,
+}
+
+impl GridLayout {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            columns: settings.columns,
+            rows: settings.rows,
+            emoji_size: settings.emoji_size,
+            spacing: SPACING,
+            span: 1,
+        }
+    }
+
+    /// The same grid for wide text such as kaomoji: each item spans [`WIDE_SPAN`] cells.
+    pub fn wide(self) -> Self {
+        Self { span: WIDE_SPAN, ..self }
+    }
+
+    /// Items on each line of a grid.
+    pub fn items_per_line(&self) -> i32 {
+        (self.columns / self.span).max(1)
+    }
+
+    /// Width of the cell of one item.
+    pub fn item_width(&self) -> i32 {
+        self.grid_width() / self.items_per_line()
+    }
+
+    /// Font size of text wider than one emoji, in points.
+    pub fn text_size(&self) -> i32 {
+        // Readable, but small enough for a few characters per emoji cell
+        (self.emoji_size * 2 / 5).max(8)
+    }
+
+    /// Width of a grid, and of the window.
+    pub fn grid_width(&self) -> i32 {
+        (self.columns * self.emoji_size) + ((self.columns - 1) * self.spacing)
+    }
+
+    /// Height of the visible part of a grid.
+    pub fn grid_height(&self) -> i32 {
+        (self.rows * self.emoji_size) + ((self.rows - 1) * self.spacing)
+    }
+}
+
+impl Default for GridLayout {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_items_span_several_columns() {
+        let layout = GridLayout { columns: 5, rows: 6, emoji_size: 32, spacing: 12, span: 1 };
+        assert_eq!(layout.items_per_line(), 5);
+        assert_eq!(layout.item_width(), layout.grid_width() / 5);
+
+        let wide = layout.wide();
+        assert_eq!(wide.grid_width(), layout.grid_width());
+        assert_eq!(wide.items_per_line(), 5 / WIDE_SPAN);
+        assert!(wide.item_width() > layout.item_width());
+        assert_eq!(GridLayout { columns: 3, span: 4, ..layout }.items_per_line(), 1);
+    }
+}
@@ -1,51 +1,102 @@
 //! Main window for the emoji picker, using EmojiPickerController for all UI logic.
 
-use crate::emoji::emoji_data::EMOJIS;
+use crate::clipboard;
+use crate::emoji::collections;
+use crate::emoji::coverage::Coverage;
+use crate::emoji::favorites::{self, Favorites};
+use crate::emoji::skin_tone::SkinTone;
+use crate::emoji::unicode;
+use crate::emoji::usage::{self, UsageHistory};
+use crate::output::{self, Inserter};
+use crate::settings::{self, BrowseLayout, OutputMode, Settings};
+use crate::trace;
+use crate::ui::app_controller::{Catalog, EmojiPickerController, PickerMode};
 use crate::ui::category_bar::CategoryBar;
 use crate::ui::constants::*;
-use crate::ui::emoji_grid::EmojiGrid;
+use crate::ui::continuous_grid::ContinuousGrid;
+use crate::ui::coverage;
+use crate::ui::emoji_grid::{EmojiGrid, GridCallbacks, SharedSkinTones};
+use crate::ui::layout::GridLayout;
+use crate::ui::preferences;
+use crate::ui::preview::{PreviewPane, PREVIEW_HEIGHT};
 use crate::ui::style;
 use gtk4::prelude::*;
 use gtk4::Stack;
-use gtk4::{Application, ApplicationWindow};
+use gtk4::ApplicationWindow;
+use libadwaita as adw;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 
+/// Height reserved for the category bar above the grids.
+const CATEGORY_BAR_HEIGHT: i32 = 44;
+
+/// Stack page showing the search results.
+const SEARCH_PAGE: &str = "__search__";
+/// Stack page showing the Unicode block chosen in the block selector.
+const UNICODE_PAGE: &str = "__unicode__";
+/// Stack page showing all categories in one grid, in the continuous browse layout.
+const CONTINUOUS_PAGE: &str = "__all__";
+
+#[derive(Clone)]
 pub struct MainWindow {
     window: ApplicationWindow,
+    search_entry: gtk4::Entry,
+    controller: Rc<RefCell<EmojiPickerController>>,
+    /// Whether closing the window only hides it (see [`MainWindow::new`]).
+    background: bool,
+    /// Holds the category bar and the grids, which are rebuilt when their settings change.
+    content: gtk4::Box,
+    /// The layout, browse layout, locales and newest emoji version `content` was last built with.
+    built_with: Rc<RefCell<Option<(GridLayout, BrowseLayout, Vec<&'static str>, Option<(u8, u8)>)>>>,
+    /// Every grid renders with the same skin tone preference; all_grids lets a change redraw them
+    skin_tones: SharedSkinTones,
+    all_grids: Rc<RefCell<Vec<EmojiGrid>>>,
+    /// All categories in one grid, in the continuous browse layout
+    continuous_grid: Rc<RefCell<Option<ContinuousGrid>>>,
+    tone_selector: gtk4::DropDown,
+    /// Switches between emoji and all Unicode characters (see [`Catalog`])
+    catalog_toggle: gtk4::ToggleButton,
+    /// Text insertion backends, looked up once; without any, picks go to the clipboard
+    inserters: Rc<Vec<Inserter>>,
+    /// Closes the window once another application takes the clipboard over (see
+    /// [`MainWindow::close_after_copy`])
+    clipboard_watch: Rc<RefCell<Option<gtk4::glib::SignalHandlerId>>>,
 }
 
 impl MainWindow {
-    pub fn new(app: &Application) -> Self {
-        let grid_rows = ROWS;
-        let grid_columns = COLUMNS;
-        let emoji_size = EMOJI_SIZE;
-        let spacing = SPACING;
-        let grid_width = (grid_columns * emoji_size) + ((grid_columns - 1) * spacing);
-
-        let categorybar_height = 44;
-        let grid_height = (grid_rows * emoji_size) + ((grid_rows - 1) * spacing);
-        let window_height = categorybar_height + grid_height;
-
-        let mut categories: Vec<&str> = EMOJIS.iter().map(|e| e.category).collect();
-        categories.sort();
-        categories.dedup();
-
-        let stack = Stack::new();
+    /// Build the picker window without showing it; call [`MainWindow::present`] for that.
+    ///
+    /// A `background` window is reused across activations: closing it only hides it, and
+    /// hiding it clears the search so the next activation starts from the category view.
+    pub fn new(app: &adw::Application, background: bool) -> Self {
+        let usage_path = usage::default_path();
+        let usage_history = UsageHistory::load(&usage_path, RECENT_CAPACITY);
+        let all_emojis = collections::all_emojis();
+        let settings_path = settings::default_path();
+        let settings = Settings::load(&settings_path);
+        let favorites_path = favorites::default_path();
+        let favorites = Favorites::load(&favorites_path);
 
-        let mut emoji_grids = Vec::new();
-        for &category in categories.iter() {
-            let all_emojis: Vec<_> = EMOJIS.iter().filter(|e| e.category == category).collect();
-            let emoji_grid = EmojiGrid::new(&all_emojis, grid_width, grid_height);
-            stack.add_named(&emoji_grid.scrolled, Some(category));
-            emoji_grids.push(emoji_grid);
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Emoji Picker")
+            .resizable(false)
+            .build();
+        window.set_hide_on_close(background);
+        if trace::enabled() {
+            trace_first_frame(&window);
         }
-        // search_results_grid is now managed by Rc<RefCell<...>>
-        let search_results_grid = std::rc::Rc::new(std::cell::RefCell::new(EmojiGrid::new(&[], grid_width, grid_height)));
-        // Add the search results grid to the stack with the name "__search__"
-        stack.add_named(&search_results_grid.borrow().scrolled, Some("__search__"));
 
-        let category_bar = CategoryBar::new(&categories, &stack, grid_width);
+        let controller = Rc::new(RefCell::new(
+            EmojiPickerController::with_usage(all_emojis, usage_history, Some(usage_path))
+                .with_settings(settings, Some(settings_path))
+                .with_favorites(favorites, Some(favorites_path)),
+        ));
+        let skin_tones: SharedSkinTones = Rc::new(RefCell::new(controller.borrow().settings.skin_tone.clone()));
 
-    // Wire up focus transfer between category bar and emoji grid
+        let inserters = Rc::new(Inserter::detect());
+        log::info!("Text insertion backends: {:?}", inserters);
 
         let vbox = gtk4::Box::builder()
             .orientation(gtk4::Orientation::Vertical)
@@ -59,77 +110,107 @@ impl MainWindow {
             .build();
 
         let mut search_bar = crate::ui::search::SearchBar::new();
-        let category_scrolled = gtk4::ScrolledWindow::builder()
-            .child(&category_bar.button_bar)
-            .hscrollbar_policy(gtk4::PolicyType::Always)
-            .min_content_width(grid_width)
-            .min_content_height(60)
-            .build();
+        search_bar.widget().set_hexpand(true);
 
-        vbox.append(search_bar.widget());
-        vbox.append(&category_scrolled);
-        vbox.append(&stack);
+        // Global skin tone selector next to the search entry
+        let tone_labels: Vec<String> =
+            SkinTone::ALL.iter().map(|tone| format!("{}{}", SKIN_TONE_SELECTOR_EMOJI, tone.modifier())).collect();
+        let tone_labels: Vec<&str> = tone_labels.iter().map(String::as_str).collect();
+        let tone_selector = gtk4::DropDown::from_strings(&tone_labels);
+        tone_selector.set_tooltip_text(Some("Skin tone"));
+        tone_selector.add_css_class("skin-tone-selector");
 
-        let all_emojis: Vec<_> = EMOJIS.iter().cloned().collect();
-        let controller = std::rc::Rc::new(std::cell::RefCell::new(
-            crate::ui::app_controller::EmojiPickerController::new(all_emojis),
-        ));
-        let category_scrolled_clone = category_scrolled.clone();
-        let stack_clone = stack.clone();
-        let search_results_grid_clone = search_results_grid;
-        let categories_for_closure = categories.clone();
-        let _controller_for_closure = controller.clone();
-        // Register UI update listener
-        controller.borrow_mut().add_listener({
-            let category_scrolled_clone = category_scrolled_clone.clone();
-            let stack_clone = stack_clone.clone();
-            let categories_for_closure = categories_for_closure.clone();
-            let search_results_grid_ptr = std::rc::Rc::new(std::cell::RefCell::new(search_results_grid_clone));
-            move |mode, filtered_emojis: &[crate::emoji::emoji_data::Emoji]| {
-                if mode == crate::ui::app_controller::PickerMode::Search {
-                    category_scrolled_clone.set_visible(false);
-                    log::info!("UI listener: displaying {} emojis in search results grid", filtered_emojis.len());
-                    let emoji_refs: Vec<_> = filtered_emojis.iter().collect();
-                    let grid_rc = search_results_grid_ptr.borrow_mut();
-                    grid_rc.borrow_mut().update_emojis(&emoji_refs, grid_width, grid_height);
-                    stack_clone.set_visible_child_name("__search__");
-                } else {
-                    category_scrolled_clone.set_visible(true);
-                    if let Some(first_cat) = categories_for_closure.first() {
-                        stack_clone.set_visible_child_name(first_cat);
-                    }
+        let catalog_toggle = gtk4::ToggleButton::with_label("Ω");
+        catalog_toggle.set_tooltip_text(Some("Unicode characters (Ctrl+U)"));
+        catalog_toggle.add_css_class("flat");
+
+        let preferences_button = gtk4::Button::from_icon_name("preferences-system-symbolic");
+        preferences_button.set_tooltip_text(Some("Preferences"));
+        preferences_button.add_css_class("flat");
+
+        let search_row = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+        search_row.append(search_bar.widget());
+        search_row.append(&catalog_toggle);
+        search_row.append(&tone_selector);
+        search_row.append(&preferences_button);
+        let content = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+        vbox.append(&search_row);
+        vbox.append(&content);
+        window.set_child(Some(&vbox));
+
+        let main_window = Self {
+            window: window.clone(),
+            search_entry: search_bar.widget().clone(),
+            controller: controller.clone(),
+            background,
+            content,
+            built_with: Rc::new(RefCell::new(None)),
+            skin_tones,
+            all_grids: Rc::new(RefCell::new(Vec::new())),
+            continuous_grid: Rc::new(RefCell::new(None)),
+            tone_selector: tone_selector.clone(),
+            catalog_toggle: catalog_toggle.clone(),
+            inserters,
+            clipboard_watch: Rc::new(RefCell::new(None)),
+        };
+
+        tone_selector.connect_selected_notify({
+            let main_window = main_window.clone();
+            move |selector| {
+                let tone = SkinTone::ALL.get(selector.selected() as usize).copied().unwrap_or_default();
+                // Also notified when apply_settings syncs the selector
+                if main_window.controller.borrow().settings.skin_tone.default != tone {
+                    main_window.controller.borrow_mut().set_skin_tone(tone);
+                    main_window.apply_settings();
                 }
             }
         });
-        // Only trigger controller logic on search
-        search_bar.set_on_search(move |query| {
-            log::info!("SearchBar event: query='{}'", query);
-            controller.borrow_mut().handle_search(query);
+        catalog_toggle.connect_toggled({
+            let controller = controller.clone();
+            move |toggle| {
+                let catalog = if toggle.is_active() { Catalog::Unicode } else { Catalog::Emoji };
+                controller.borrow_mut().set_catalog(catalog);
+            }
+        });
+        preferences_button.connect_clicked({
+            let main_window = main_window.clone();
+            move |_| main_window.show_preferences()
         });
 
-        let window = ApplicationWindow::builder()
-            .application(app)
-            .title("Emoji Picker")
-            .default_width(grid_width)
-            .default_height(window_height)
-            .resizable(false)
-            .child(&vbox)
-            .build();
-        window.set_size_request(grid_width, window_height);
-                let search_results_grid = std::rc::Rc::new(std::cell::RefCell::new(EmojiGrid::new(&[], grid_width, grid_height)));
+        // Only trigger controller logic on search
+        search_bar.set_on_search({
+            let controller = controller.clone();
+            move |query| {
+                log::info!("SearchBar event: query='{}'", query);
+                controller.borrow_mut().handle_search(query);
+            }
+        });
 
-        // No close-on-select for search_results_grid: just copy on Enter (handled in EmojiGrid)
+        // Copy mode keeps the window open unless close_on_copy is set (see `deliver`).
+        // Insert mode hides the window to type into the previous application.
 
         // Add Escape key handler to close the window
         let window_clone = window.clone();
         let search_entry = search_bar.widget().clone();
+        let main_window_clone = main_window.clone();
         let key_controller = gtk4::EventControllerKey::new();
-        key_controller.connect_key_pressed(move |_, keyval, _keycode, _state| {
+        key_controller.connect_key_pressed(move |_, keyval, _keycode, state| {
             // Close window on Escape
             if keyval == gtk4::gdk::Key::Escape {
                 window_clone.close();
                 return gtk4::glib::signal::Propagation::Stop;
             }
+            // Ctrl+, opens the preferences, as in other GNOME applications
+            if keyval == gtk4::gdk::Key::comma && state.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
+                main_window_clone.show_preferences();
+                return gtk4::glib::signal::Propagation::Stop;
+            }
+            // Ctrl+U switches between emoji and Unicode characters
+            if keyval == gtk4::gdk::Key::u && state.contains(gtk4::gdk::ModifierType::CONTROL_MASK) {
+                let toggle = &main_window_clone.catalog_toggle;
+                toggle.set_active(!toggle.is_active());
+                return gtk4::glib::signal::Propagation::Stop;
+            }
             // Focus search bar on typing
             if !search_entry.has_focus() {
                 if let Some(c) = keyval.to_unicode() {
@@ -143,23 +224,528 @@ impl MainWindow {
         });
         window.add_controller(key_controller);
 
-        let provider = style::setup_css();
-        gtk4::style_context_add_provider_for_display(
-            &gtk4::gdk::Display::default().expect("No default display found"),
-            &provider,
-            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-        );
+        style::install(&gtk4::gdk::Display::default().expect("No default display found"));
 
-        if let Some(first_cat) = categories.first() {
-            stack.set_visible_child_name(first_cat);
+        if background {
+            let search_entry = search_bar.widget().clone();
+            window.connect_hide(move |_| {
+                // Clearing the entry runs an empty search, which resets the stack to browsing
+                search_entry.set_text("");
+            });
         }
 
-        window.show();
+        main_window.apply_settings();
+        // Emoji the fonts cannot show are left out, rather than shown as boxes, once the
+        // check is done; until then every emoji is shown
+        let emoji_size = controller.borrow().settings.emoji_size;
+        coverage::check_later(emoji_size, {
+            let main_window = main_window.clone();
+            move |coverage| main_window.set_coverage(coverage)
+        });
+        main_window
+    }
 
-        Self { window }
+    /// Leave out the emoji that `coverage` says the fonts cannot show, rebuilding the grids
+    /// when that hides any.
+    fn set_coverage(&self, coverage: Coverage) {
+        let hides_any = coverage.unsupported_count() > 0;
+        self.controller.borrow_mut().coverage = coverage;
+        if hides_any {
+            *self.built_with.borrow_mut() = None;
+            self.apply_settings();
+        }
     }
-    
+
     pub fn present(&self) {
+        self.stop_clipboard_watch();
         self.window.present();
+        self.search_entry.grab_focus();
     }
+
+    /// Present the window searching for `query`, or browsing when it is empty.
+    pub fn show_search(&self, query: &str) {
+        self.search_entry.set_text(query);
+        self.present();
+        self.search_entry.set_position(-1);
+    }
+
+    /// The controller behind this window, shared with the D-Bus interface.
+    pub fn controller(&self) -> Rc<RefCell<EmojiPickerController>> {
+        self.controller.clone()
+    }
+
+    /// Open the preferences window. Every change is saved and applied right away.
+    pub fn show_preferences(&self) {
+        let settings = self.controller.borrow().settings.clone();
+        let main_window = self.clone();
+        preferences::show(&self.window, &settings, move |update| {
+            main_window.controller.borrow_mut().update_settings(update);
+            main_window.apply_settings();
+        });
+    }
+
+    /// Bring the window in line with the controller's settings.
+    ///
+    /// The theme and skin tones are applied in place; the grids are only rebuilt when their
+    /// layout, browse layout, locales or newest emoji version changed, as that recreates every
+    /// page.
+    pub fn apply_settings(&self) {
+        let (settings, locales) = {
+            let controller = self.controller.borrow();
+            (controller.settings.clone(), controller.locales.clone())
+        };
+        style::apply_theme(settings.theme);
+
+        let current_index = SkinTone::ALL.iter().position(|&t| t == settings.skin_tone.default).unwrap_or(0);
+        self.tone_selector.set_selected(current_index as u32);
+        *self.skin_tones.borrow_mut() = settings.skin_tone.clone();
+        for grid in self.all_grids.borrow().iter() {
+            grid.refresh_skin_tones();
+        }
+        if let Some(ref grid) = *self.continuous_grid.borrow() {
+            grid.refresh_skin_tones();
+        }
+
+        let wanted =
+            (GridLayout::from_settings(&settings), settings.browse_layout, locales, settings.max_emoji_version());
+        if self.built_with.borrow().as_ref() != Some(&wanted) {
+            self.build_content(wanted.0, wanted.1, &wanted.2);
+            *self.built_with.borrow_mut() = Some(wanted);
+        }
+    }
+
+    /// Build the category bar and the grids for `layout`, with the categories on pages of
+    /// their own or in one grid as `browse` says, replacing any previous ones.
+    fn build_content(&self, layout: GridLayout, browse: BrowseLayout, locales: &[&'static str]) {
+        let grid_width = layout.grid_width();
+        let grid_height = layout.grid_height();
+        let window_height = CATEGORY_BAR_HEIGHT + grid_height + PREVIEW_HEIGHT;
+        self.window.set_default_size(grid_width, window_height);
+        self.window.set_size_request(grid_width, window_height);
+
+        while let Some(child) = self.content.first_child() {
+            self.content.remove(&child);
+        }
+        self.all_grids.borrow_mut().clear();
+        *self.continuous_grid.borrow_mut() = None;
+        // The listeners update the widgets that are being replaced
+        self.controller.borrow_mut().clear_listeners();
+
+        let categories = self.controller.borrow().categories();
+        let locales = Rc::new(locales.to_vec());
+        let controller = &self.controller;
+        let skin_tones = &self.skin_tones;
+        let all_grids = &self.all_grids;
+
+        let stack = Stack::new();
+
+        // The preview under the grids describes the selection of the page on show
+        let preview = PreviewPane::new(grid_width, skin_tones, &locales);
+        let connect_preview = {
+            let preview = preview.clone();
+            let stack = stack.clone();
+            move |callbacks: &GridCallbacks, scrolled: &gtk4::ScrolledWindow| {
+                let preview = preview.clone();
+                let stack = stack.clone();
+                let scrolled = scrolled.clone();
+                callbacks.set_on_selection_changed(move |emoji| {
+                    if stack.visible_child().is_some_and(|page| shows_page(&scrolled, &page)) {
+                        preview.show(emoji);
+                    }
+                });
+            }
+        };
+
+        // The synthetic "Favorites" page holds the pinned emoji, in the user's order
+        let favorite_emojis = controller.borrow().favorite_emojis();
+        let favorite_refs: Vec<_> = favorite_emojis.iter().collect();
+        let favorites_grid = Rc::new(RefCell::new(EmojiGrid::new(&favorite_refs, layout, skin_tones, &locales)));
+        stack.add_named(&favorites_grid.borrow().scrolled, Some(FAVORITES_CATEGORY));
+        // Rebuild the Favorites page after a change, selecting the favorite at the given index.
+        // Deferred, as the change may have come from a key press in that grid itself
+        let refresh_favorites: Rc<dyn Fn(Option<usize>)> = {
+            let controller = controller.clone();
+            let favorites_grid = favorites_grid.clone();
+            Rc::new(move |selected| {
+                let controller = controller.clone();
+                let favorites_grid = favorites_grid.clone();
+                gtk4::glib::idle_add_local_once(move || {
+                    let favorites = controller.borrow().favorite_emojis();
+                    let refs: Vec<_> = favorites.iter().collect();
+                    favorites_grid.borrow_mut().update_emojis(&refs);
+                    if let Some(index) = selected {
+                        favorites_grid.borrow().select(index);
+                    }
+                });
+            })
+        };
+        favorites_grid.borrow().callbacks.set_on_favorite_moved({
+            let controller = controller.clone();
+            let refresh_favorites = refresh_favorites.clone();
+            move |ch, offset| {
+                let position = controller.borrow_mut().move_favorite(ch, offset);
+                if position.is_some() {
+                    refresh_favorites(position);
+                }
+            }
+        });
+        // Every grid can pin and unpin its emoji
+        let connect_favorites = {
+            let controller = controller.clone();
+            move |callbacks: &GridCallbacks| {
+                let query = controller.clone();
+                callbacks.set_is_favorite(move |ch| query.borrow().is_favorite(ch));
+                let toggled = controller.clone();
+                let refresh_favorites = refresh_favorites.clone();
+                callbacks.set_on_favorite_toggled(move |ch| {
+                    toggled.borrow_mut().toggle_favorite(ch);
+                    refresh_favorites(None);
+                });
+            }
+        };
+        connect_favorites(&favorites_grid.borrow().callbacks);
+        connect_preview(&favorites_grid.borrow().callbacks, &favorites_grid.borrow().scrolled);
+
+        // The synthetic "Recent" page comes right after Favorites and is rebuilt whenever an emoji
+        // is copied
+        let recent_emojis = controller.borrow().recent_emojis();
+        let recent_refs: Vec<_> = recent_emojis.iter().collect();
+        let recent_grid = Rc::new(RefCell::new(EmojiGrid::new(&recent_refs, layout, skin_tones, &locales)));
+        stack.add_named(&recent_grid.borrow().scrolled, Some(RECENT_CATEGORY));
+        let on_emoji_copied: Rc<dyn Fn(&str)> = {
+            let main_window = self.clone();
+            let recent_grid = recent_grid.clone();
+            Rc::new(move |ch: &str| {
+                main_window.deliver(ch);
+                main_window.controller.borrow_mut().record_pick(ch);
+                let controller = main_window.controller.clone();
+                let recent_grid = recent_grid.clone();
+                // Defer the rebuild: the copy may have come from the Recent grid itself
+                gtk4::glib::idle_add_local_once(move || {
+                    let recent = controller.borrow().recent_emojis();
+                    let refs: Vec<_> = recent.iter().collect();
+                    recent_grid.borrow_mut().update_emojis(&refs);
+                });
+            })
+        };
+        let on_skin_tone_chosen: Rc<dyn Fn(&str, SkinTone)> = {
+            let main_window = self.clone();
+            Rc::new(move |ch: &str, tone| {
+                main_window.controller.borrow_mut().set_emoji_skin_tone(ch, tone);
+                main_window.apply_settings();
+            })
+        };
+        // Picking an emoji and choosing its tone work the same in every grid
+        let connect_picks = {
+            let on_emoji_copied = on_emoji_copied.clone();
+            let on_skin_tone_chosen = on_skin_tone_chosen.clone();
+            let controller = controller.clone();
+            move |callbacks: &GridCallbacks| {
+                let on_copied = on_emoji_copied.clone();
+                callbacks.set_on_emoji_copied(move |ch| on_copied(ch));
+                let on_chosen = on_skin_tone_chosen.clone();
+                callbacks.set_on_skin_tone_chosen(move |ch, tone| on_chosen(ch, tone));
+                let coverage = controller.clone();
+                callbacks.set_is_supported(move |text| coverage.borrow().coverage.is_supported(text));
+            }
+        };
+        connect_picks(&recent_grid.borrow().callbacks);
+        connect_favorites(&recent_grid.borrow().callbacks);
+        connect_preview(&recent_grid.borrow().callbacks, &recent_grid.borrow().scrolled);
+        all_grids.borrow_mut().push(recent_grid.borrow().clone());
+        connect_picks(&favorites_grid.borrow().callbacks);
+        all_grids.borrow_mut().push(favorites_grid.borrow().clone());
+
+        // In the continuous layout, all categories share one page, built right away
+        let shown_emojis = controller.borrow().shown_emojis();
+        let continuous_grid = (browse == BrowseLayout::Continuous).then(|| {
+            let sections = categories
+                .iter()
+                .map(|&category| (category, shown_emojis.iter().filter(|e| e.category == category).cloned().collect()))
+                .collect();
+            let grid = ContinuousGrid::new(sections, layout, skin_tones, &locales);
+            connect_picks(&grid.callbacks);
+            connect_favorites(&grid.callbacks);
+            connect_preview(&grid.callbacks, &grid.scrolled);
+            stack.add_named(&grid.widget, Some(CONTINUOUS_PAGE));
+            *self.continuous_grid.borrow_mut() = Some(grid.clone());
+            grid
+        });
+        // Otherwise category pages start out as empty placeholders and get their grid the first
+        // time they are shown (see build_category_page), so startup only builds the page in view
+        let placeholders = RefCell::new(HashMap::new());
+        if continuous_grid.is_none() {
+            for &category in categories.iter() {
+                let placeholder = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+                stack.add_named(&placeholder, Some(category));
+                placeholders.borrow_mut().insert(category, placeholder);
+            }
+        }
+        let build_category_page = {
+            let skin_tones = skin_tones.clone();
+            let locales = locales.clone();
+            let connect_picks = connect_picks.clone();
+            let connect_favorites = connect_favorites.clone();
+            let connect_preview = connect_preview.clone();
+            let all_grids = all_grids.clone();
+            move |category: &str, placeholder: &gtk4::Box| {
+                let category_emojis: Vec<_> = shown_emojis.iter().filter(|e| e.category == category).collect();
+                // Kaomoji and text snippets get wider cells than emoji
+                let layout = if category_emojis.iter().any(|e| e.is_wide()) { layout.wide() } else { layout };
+                let emoji_grid = EmojiGrid::new(&category_emojis, layout, &skin_tones, &locales);
+                connect_picks(&emoji_grid.callbacks);
+                connect_favorites(&emoji_grid.callbacks);
+                connect_preview(&emoji_grid.callbacks, &emoji_grid.scrolled);
+                placeholder.append(&emoji_grid.scrolled);
+                all_grids.borrow_mut().push(emoji_grid);
+                trace::mark(&format!("built page {}", category));
+            }
+        };
+        // search_results_grid is now managed by Rc<RefCell<...>>
+        let search_results_grid = Rc::new(RefCell::new(EmojiGrid::new(&[], layout, skin_tones, &locales)));
+        connect_picks(&search_results_grid.borrow().callbacks);
+        connect_favorites(&search_results_grid.borrow().callbacks);
+        connect_preview(&search_results_grid.borrow().callbacks, &search_results_grid.borrow().scrolled);
+        all_grids.borrow_mut().push(search_results_grid.borrow().clone());
+        // Add the search results grid to the stack with the name "__search__"
+        stack.add_named(&search_results_grid.borrow().scrolled, Some(SEARCH_PAGE));
+
+        // Unicode characters are browsed one block at a time, picked from a searchable list
+        let blocks = unicode::blocks();
+        let block_selector = gtk4::DropDown::from_strings(&blocks);
+        block_selector.set_enable_search(true);
+        block_selector.set_expression(Some(gtk4::PropertyExpression::new(
+            gtk4::StringObject::static_type(),
+            None::<gtk4::Expression>,
+            "string",
+        )));
+        block_selector.set_tooltip_text(Some("Unicode block"));
+        let unicode_grid = Rc::new(RefCell::new(EmojiGrid::new(&[], layout, skin_tones, &locales)));
+        unicode_grid.borrow().grid_view.add_css_class("unicode-characters");
+        let on_copied = on_emoji_copied.clone();
+        unicode_grid.borrow().callbacks.set_on_emoji_copied(move |ch| on_copied(ch));
+        connect_favorites(&unicode_grid.borrow().callbacks);
+        connect_preview(&unicode_grid.borrow().callbacks, &unicode_grid.borrow().scrolled);
+        all_grids.borrow_mut().push(unicode_grid.borrow().clone());
+        stack.add_named(&unicode_grid.borrow().scrolled, Some(UNICODE_PAGE));
+        let show_block = {
+            let unicode_grid = unicode_grid.clone();
+            move |selector: &gtk4::DropDown| {
+                let Some(&block) = blocks.get(selector.selected() as usize) else { return };
+                let characters: Vec<_> = unicode::in_block(block).iter().collect();
+                unicode_grid.borrow_mut().update_emojis(&characters);
+            }
+        };
+        show_block(&block_selector);
+        block_selector.connect_selected_notify(show_block);
+
+        let mut tabs = vec![FAVORITES_CATEGORY, RECENT_CATEGORY];
+        tabs.extend(categories.iter().copied());
+        // A tab shows the page of its category, or scrolls the continuous grid to its section
+        let show_tab = {
+            let stack = stack.clone();
+            let continuous_grid = continuous_grid.clone();
+            move |category: &str| match continuous_grid {
+                Some(ref grid) if grid.section_index(category).is_some() => {
+                    stack.set_visible_child_name(CONTINUOUS_PAGE);
+                    grid.scroll_to_section(category);
+                }
+                _ => stack.set_visible_child_name(category),
+            }
+        };
+        let category_bar = CategoryBar::new(&tabs, show_tab, grid_width);
+        if let Some(ref grid) = continuous_grid {
+            let category_bar = category_bar.clone();
+            grid.set_on_section_shown(move |category| category_bar.set_active(Some(category)));
+        }
+
+        let category_scrolled = gtk4::ScrolledWindow::builder()
+            .child(&category_bar.button_bar)
+            .hscrollbar_policy(gtk4::PolicyType::Always)
+            .min_content_width(grid_width)
+            .min_content_height(60)
+            .build();
+
+        self.content.append(&category_scrolled);
+        self.content.append(&block_selector);
+        self.content.append(&stack);
+        self.content.append(&preview.widget);
+        stack.connect_visible_child_notify({
+            let all_grids = all_grids.clone();
+            let continuous_grid = continuous_grid.clone();
+            move |stack| {
+                let Some(page) = stack.visible_child() else { return };
+                let name = stack.visible_child_name();
+                // A category shown for the first time, from the category bar or otherwise
+                if let Some(ref name) = name {
+                    let placeholder = placeholders.borrow_mut().remove(name.as_str());
+                    if let Some(placeholder) = placeholder {
+                        build_category_page(name, &placeholder);
+                    }
+                }
+                // The tab of the page in view, or of the section at the top of the continuous grid
+                match continuous_grid {
+                    Some(ref grid) if name.as_deref() == Some(CONTINUOUS_PAGE) => {
+                        category_bar.set_active(grid.section_in_view());
+                        preview.show(grid.selected_emoji().as_ref());
+                        return;
+                    }
+                    _ => category_bar.set_active(name.as_deref()),
+                }
+                let grid = all_grids.borrow().iter().find(|g| shows_page(&g.scrolled, &page)).cloned();
+                preview.show(grid.and_then(|g| g.selected_emoji()).as_ref());
+            }
+        });
+
+        // Browsing shows the category tabs for emoji and the block selector for Unicode
+        let show_browse = {
+            let catalog_toggle = self.catalog_toggle.clone();
+            let category_scrolled = category_scrolled.clone();
+            let block_selector = block_selector.clone();
+            let stack = stack.clone();
+            let recent_grid = recent_grid.clone();
+            let continuous = continuous_grid.is_some();
+            move || {
+                let unicode = catalog_toggle.is_active();
+                category_scrolled.set_visible(!unicode);
+                block_selector.set_visible(unicode);
+                if unicode {
+                    stack.set_visible_child_name(UNICODE_PAGE);
+                } else if !favorites_grid.borrow().is_empty() {
+                    // Start on the "Favorites" page once there are any
+                    stack.set_visible_child_name(FAVORITES_CATEGORY);
+                } else if !recent_grid.borrow().is_empty() {
+                    // Start on the "Recent" page once there is any history
+                    stack.set_visible_child_name(RECENT_CATEGORY);
+                } else if continuous {
+                    stack.set_visible_child_name(CONTINUOUS_PAGE);
+                } else if let Some(first_cat) = categories.first() {
+                    stack.set_visible_child_name(first_cat);
+                }
+            }
+        };
+        show_browse();
+
+        // Register UI update listener
+        controller.borrow_mut().add_listener({
+            let catalog_toggle = self.catalog_toggle.clone();
+            let category_scrolled = category_scrolled.clone();
+            let stack = stack.clone();
+            move |mode, filtered_emojis: &[crate::emoji::emoji_data::Emoji]| {
+                if mode == PickerMode::Search {
+                    category_scrolled.set_visible(false);
+                    block_selector.set_visible(false);
+                    log::info!("UI listener: displaying {} emojis in search results grid", filtered_emojis.len());
+                    let emoji_refs: Vec<_> = filtered_emojis.iter().collect();
+                    // Unicode characters are shown in a text font, not as emoji
+                    let grid_view = search_results_grid.borrow().grid_view.clone();
+                    if catalog_toggle.is_active() {
+                        grid_view.add_css_class("unicode-characters");
+                    } else {
+                        grid_view.remove_css_class("unicode-characters");
+                    }
+                    search_results_grid.borrow_mut().update_emojis(&emoji_refs);
+                    stack.set_visible_child_name(SEARCH_PAGE);
+                } else {
+                    show_browse();
+                }
+            }
+        });
+        // Show a running search in the new grids
+        let query = controller.borrow().search_query.clone();
+        if !query.is_empty() {
+            controller.borrow_mut().handle_search(&query);
+        }
+    }
+
+    /// Deliver a picked emoji according to the output mode.
+    ///
+    /// In insert mode the window is hidden first, so focus returns to the application the user
+    /// came from, and the emoji is typed there. Without a working insertion backend the emoji
+    /// is copied to the clipboard instead, and the window comes back.
+    fn deliver(&self, text: &str) {
+        let (mode, close_on_copy) = {
+            let settings = &self.controller.borrow().settings;
+            (settings.output_mode, settings.close_on_copy)
+        };
+        if mode == OutputMode::Copy || self.inserters.is_empty() {
+            clipboard::copy(text);
+            if close_on_copy {
+                self.close_after_copy();
+            }
+            return;
+        }
+        let window = self.window.clone();
+        window.set_visible(false);
+        let text = text.to_string();
+        let inserters = self.inserters.clone();
+        gtk4::glib::timeout_add_local_once(output::INSERT_DELAY, move || {
+            gtk4::glib::spawn_future_local(async move {
+                match output::insert_with(&inserters, &text).await {
+                    Ok(inserter) => {
+                        log::info!("Inserted {} with {}", text, inserter.program());
+                        window.close();
+                    }
+                    Err(err) => {
+                        log::warn!("Could not insert {}, copying it instead: {}", text, err);
+                        clipboard::copy(&text);
+                        window.present();
+                    }
+                }
+            });
+        });
+    }
+
+    /// Close the window after copying without losing the copied emoji.
+    ///
+    /// The clipboard refers to this process, so a window that is not kept in the background
+    /// only hides until another application takes the clipboard over.
+    fn close_after_copy(&self) {
+        if self.background {
+            self.window.close();
+            return;
+        }
+        self.window.set_visible(false);
+        self.stop_clipboard_watch();
+        let window = self.window.clone();
+        let watch = self.clipboard_watch.clone();
+        let id = self.window.clipboard().connect_changed(move |clipboard| {
+            if !clipboard.is_local() {
+                if let Some(id) = watch.borrow_mut().take() {
+                    clipboard.disconnect(id);
+                }
+                window.close();
+            }
+        });
+        *self.clipboard_watch.borrow_mut() = Some(id);
+    }
+
+    /// Stop waiting for the clipboard to be taken over, as the window is shown again or
+    /// another copy starts a new wait.
+    fn stop_clipboard_watch(&self) {
+        if let Some(id) = self.clipboard_watch.borrow_mut().take() {
+            self.window.clipboard().disconnect(id);
+        }
+    }
+}
+
+/// Whether `page` of the stack shows the grid in `scrolled`: the grid is the page itself, or
+/// fills the placeholder of a category page.
+fn shows_page(scrolled: &gtk4::ScrolledWindow, page: &gtk4::Widget) -> bool {
+    scrolled.upcast_ref::<gtk4::Widget>() == page || scrolled.parent().as_ref() == Some(page)
+}
+
+/// Mark the first frame the window paints in the startup trace.
+fn trace_first_frame(window: &ApplicationWindow) {
+    window.connect_realize(|window| {
+        let Some(clock) = window.frame_clock() else { return };
+        let handler = Rc::new(RefCell::new(None));
+        let handler_clone = handler.clone();
+        *handler.borrow_mut() = Some(clock.connect_after_paint(move |clock| {
+            trace::mark("first frame");
+            if let Some(id) = handler_clone.borrow_mut().take() {
+                clock.disconnect(id);
+            }
+        }));
+    });
 }
@@ -1,9 +1,15 @@
 pub mod emoji_label;
 pub mod constants;
+pub mod layout;
 pub mod style;
 pub mod category_bar;
 pub mod emoji_grid;
+pub mod sections;
+pub mod continuous_grid;
 pub mod search;
 pub mod main_window;
+pub mod preferences;
+pub mod preview;
 pub mod app_controller;
+pub mod coverage;
 
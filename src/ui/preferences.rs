@@ -0,0 +1,194 @@
+//! Preferences window, editing the settings of [`crate::settings`].
+//!
+//! Every change is handed to the caller right away, which saves it and applies it to the
+//! picker, so there is no apply button.
+
+use crate::emoji::emoji_data::{EMOJIS, LOCALES};
+use crate::emoji::skin_tone::SkinTone;
+use crate::settings::{BrowseLayout, OutputMode, Settings, Theme, COLUMNS_RANGE, EMOJI_SIZE_RANGE, ROWS_RANGE};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+/// Choices of the theme row, in the order shown.
+const THEMES: [(Theme, &str); 3] = [(Theme::System, "Follow system"), (Theme::Light, "Light"), (Theme::Dark, "Dark")];
+
+/// Choices of the browse layout row, in the order shown.
+const BROWSE_LAYOUTS: [(BrowseLayout, &str); 2] =
+    [(BrowseLayout::Paged, "One page per category"), (BrowseLayout::Continuous, "All categories in one grid")];
+
+/// Choices of the output mode row, in the order shown.
+const OUTPUT_MODES: [(OutputMode, &str); 2] =
+    [(OutputMode::Copy, "Copy to clipboard"), (OutputMode::Insert, "Type into the previous window")];
+
+/// Called with each change made in the window.
+type OnChange = Rc<dyn Fn(&dyn Fn(&mut Settings))>;
+
+/// Show the preferences for `settings` over `parent`, calling `on_change` for every change.
+pub fn show<F>(parent: &impl IsA<gtk4::Window>, settings: &Settings, on_change: F)
+where
+    F: Fn(&dyn Fn(&mut Settings)) + 'static,
+{
+    let on_change: OnChange = Rc::new(on_change);
+    let page = adw::PreferencesPage::new();
+    page.add(&appearance_group(settings, &on_change));
+    page.add(&behavior_group(settings, &on_change));
+    page.add(&locales_group(settings, &on_change));
+
+    let window = adw::PreferencesWindow::builder()
+        .title("Preferences")
+        .modal(true)
+        .transient_for(parent)
+        .search_enabled(false)
+        .build();
+    window.add(&page);
+    window.present();
+}
+
+fn appearance_group(settings: &Settings, on_change: &OnChange) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder().title("Appearance").build();
+
+    let labels: Vec<&str> = THEMES.iter().map(|&(_, label)| label).collect();
+    let selected = THEMES.iter().position(|&(theme, _)| theme == settings.theme).unwrap_or(0);
+    group.add(&combo_row("Style", &labels, selected, on_change, |settings, index| {
+        settings.theme = THEMES[index].0;
+    }));
+    let labels: Vec<&str> = BROWSE_LAYOUTS.iter().map(|&(_, label)| label).collect();
+    let selected = BROWSE_LAYOUTS.iter().position(|&(layout, _)| layout == settings.browse_layout).unwrap_or(0);
+    group.add(&combo_row("Categories", &labels, selected, on_change, |settings, index| {
+        settings.browse_layout = BROWSE_LAYOUTS[index].0;
+    }));
+    // Every emoji version, newest first, after the choice to show them all
+    let mut versions: Vec<(u8, u8)> = EMOJIS.iter().map(|e| e.version).collect();
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    versions.dedup();
+    let mut labels = vec!["All".to_string()];
+    labels.extend(versions.iter().map(|(major, minor)| format!("E{}.{}", major, minor)));
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let selected =
+        settings.max_emoji_version().and_then(|max| versions.iter().position(|&v| v == max)).map_or(0, |i| i + 1);
+    group.add(&combo_row("Newest emoji", &labels, selected, on_change, move |settings, index| {
+        settings.max_emoji_version = index.checked_sub(1).map(|i| format!("{}.{}", versions[i].0, versions[i].1));
+    }));
+    group.add(&spin_row("Emoji size", EMOJI_SIZE_RANGE, settings.emoji_size, on_change, |settings, value| {
+        settings.emoji_size = value;
+    }));
+    group.add(&spin_row("Columns", COLUMNS_RANGE, settings.columns, on_change, |settings, value| {
+        settings.columns = value;
+    }));
+    group.add(&spin_row("Visible rows", ROWS_RANGE, settings.rows, on_change, |settings, value| {
+        settings.rows = value;
+    }));
+    group
+}
+
+fn behavior_group(settings: &Settings, on_change: &OnChange) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder().title("Behavior").build();
+
+    let labels: Vec<&str> = OUTPUT_MODES.iter().map(|&(_, label)| label).collect();
+    let selected = OUTPUT_MODES.iter().position(|&(mode, _)| mode == settings.output_mode).unwrap_or(0);
+    group.add(&combo_row("Picked emoji", &labels, selected, on_change, |settings, index| {
+        settings.output_mode = OUTPUT_MODES[index].0;
+    }));
+
+    let close_on_copy = adw::ActionRow::builder()
+        .title("Close after copying")
+        .subtitle("Typing into the previous window always closes the picker")
+        .build();
+    let switch = gtk4::Switch::builder().active(settings.close_on_copy).valign(gtk4::Align::Center).build();
+    let on_change_clone = on_change.clone();
+    switch.connect_active_notify(move |switch| {
+        let active = switch.is_active();
+        on_change_clone(&|settings| settings.close_on_copy = active);
+    });
+    close_on_copy.add_suffix(&switch);
+    close_on_copy.set_activatable_widget(Some(&switch));
+    group.add(&close_on_copy);
+
+    let labels: Vec<&str> = SkinTone::ALL.iter().map(|tone| tone.label()).collect();
+    let selected = SkinTone::ALL.iter().position(|&tone| tone == settings.skin_tone.default).unwrap_or(0);
+    group.add(&combo_row("Skin tone", &labels, selected, on_change, |settings, index| {
+        settings.skin_tone.default = SkinTone::ALL[index];
+    }));
+    group
+}
+
+/// One switch per compiled-in locale. The enabled ones are searched in the order of
+/// [`LOCALES`]; with none enabled, the picker follows the environment.
+fn locales_group(settings: &Settings, on_change: &OnChange) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Search languages")
+        .description("Names and keywords to search; without any, the system languages are used")
+        .build();
+    let configured = settings.search_locales();
+    let switches: Rc<Vec<(&'static str, gtk4::Switch)>> = Rc::new(
+        LOCALES
+            .iter()
+            .map(|&locale| {
+                let active = !settings.locales.is_empty() && configured.contains(&locale);
+                (locale, gtk4::Switch::builder().active(active).valign(gtk4::Align::Center).build())
+            })
+            .collect(),
+    );
+    for (locale, switch) in switches.iter() {
+        let row = adw::ActionRow::builder().title(*locale).build();
+        let on_change = on_change.clone();
+        let switches_clone = switches.clone();
+        switch.connect_active_notify(move |_| {
+            let locales: Vec<String> = switches_clone
+                .iter()
+                .filter(|(_, switch)| switch.is_active())
+                .map(|(locale, _)| locale.to_string())
+                .collect();
+            on_change(&|settings| settings.locales = locales.clone());
+        });
+        row.add_suffix(switch);
+        row.set_activatable_widget(Some(switch));
+        group.add(&row);
+    }
+    group
+}
+
+/// A row choosing one of `labels`, passing the index of the choice to `update`.
+fn combo_row(
+    title: &str,
+    labels: &[&str],
+    selected: usize,
+    on_change: &OnChange,
+    update: impl Fn(&mut Settings, usize) + 'static,
+) -> adw::ComboRow {
+    let row = adw::ComboRow::builder()
+        .title(title)
+        .model(&gtk4::StringList::new(labels))
+        .selected(selected as u32)
+        .build();
+    let on_change = on_change.clone();
+    row.connect_selected_notify(move |row| {
+        let index = row.selected() as usize;
+        on_change(&|settings| update(settings, index));
+    });
+    row
+}
+
+/// A row with a spin button for a value in `range`, passed to `update`.
+fn spin_row(
+    title: &str,
+    range: RangeInclusive<i32>,
+    value: i32,
+    on_change: &OnChange,
+    update: impl Fn(&mut Settings, i32) + 'static,
+) -> adw::ActionRow {
+    let row = adw::ActionRow::builder().title(title).build();
+    let spin = gtk4::SpinButton::with_range(*range.start() as f64, *range.end() as f64, 1.0);
+    spin.set_value(value as f64);
+    spin.set_valign(gtk4::Align::Center);
+    let on_change = on_change.clone();
+    spin.connect_value_changed(move |spin| {
+        let value = spin.value_as_int();
+        on_change(&|settings| update(settings, value));
+    });
+    row.add_suffix(&spin);
+    row.set_activatable_widget(Some(&spin));
+    row
+}
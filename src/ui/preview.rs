@@ -0,0 +1,143 @@
+//! Preview strip under the grids, describing the selected emoji.
+
+use crate::cli::code_points;
+use crate::emoji::emoji_data::Emoji;
+use crate::ui::emoji_grid::SharedSkinTones;
+use crate::ui::emoji_label::EmojiLabel;
+use gtk4::pango;
+use gtk4::prelude::*;
+use gtk4::{Align, Label, Orientation};
+use std::rc::Rc;
+
+/// Height of the preview strip; fixed, so changing the selection never moves the grids.
+pub const PREVIEW_HEIGHT: i32 = 72;
+/// Size of the emoji in the preview, in points.
+const PREVIEW_EMOJI_SIZE: i32 = 40;
+/// Size of wide text such as kaomoji in the preview, in points.
+const PREVIEW_TEXT_SIZE: i32 = 14;
+
+/// The lines of text describing an emoji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Details {
+    pub name: String,
+    pub keywords: String,
+    /// Category and code points of `shown`.
+    pub info: String,
+    /// All skin tones, or empty when there are none.
+    pub skin_tones: String,
+}
+
+impl Details {
+    /// Describe `emoji`, shown as `shown` (e.g. in a skin tone), in the first of `locales`
+    /// that has names for it.
+    pub fn of(emoji: &Emoji, shown: &str, locales: &[&str]) -> Self {
+        Self {
+            name: emoji.name(locales).to_string(),
+            keywords: emoji.keywords(locales).join(", "),
+            info: format!("{} · {}", emoji.category, code_points(shown)),
+            skin_tones: emoji.skin_tone_variants.map(|v| v.join(" ")).unwrap_or_default(),
+        }
+    }
+}
+
+/// Large rendering of the selected emoji next to its name, keywords, category, code points
+/// and skin tones. Clones share the widgets.
+#[derive(Clone)]
+pub struct PreviewPane {
+    pub widget: gtk4::Box,
+    emoji: Label,
+    name: Label,
+    keywords: Label,
+    info: Label,
+    skin_tones: Label,
+    skin_tone_preference: SharedSkinTones,
+    locales: Rc<Vec<&'static str>>,
+}
+
+/// A single-line label that is cut short rather than widening the window.
+fn detail_label(css_class: &str) -> Label {
+    let label = Label::new(None);
+    label.add_css_class(css_class);
+    label.set_xalign(0.0);
+    label.set_ellipsize(pango::EllipsizeMode::End);
+    label.set_single_line_mode(true);
+    label.set_hexpand(true);
+    label
+}
+
+impl PreviewPane {
+    /// Create an empty preview `width` pixels wide.
+    pub fn new(width: i32, skin_tones: &SharedSkinTones, locales: &Rc<Vec<&'static str>>) -> Self {
+        let widget = gtk4::Box::new(Orientation::Horizontal, 12);
+        widget.add_css_class("emoji-preview");
+        widget.set_size_request(width, PREVIEW_HEIGHT);
+        widget.set_vexpand(false);
+
+        let emoji = EmojiLabel::with_size("", PREVIEW_EMOJI_SIZE);
+        emoji.set_width_request(PREVIEW_HEIGHT);
+        emoji.set_ellipsize(pango::EllipsizeMode::End);
+        widget.append(&emoji);
+
+        let lines = gtk4::Box::new(Orientation::Vertical, 0);
+        lines.set_valign(Align::Center);
+        lines.set_hexpand(true);
+        let name = detail_label("preview-name");
+        let keywords = detail_label("dim-label");
+        let info = detail_label("dim-label");
+        let skin_tones = detail_label("preview-skin-tones");
+        for label in [&name, &keywords, &info, &skin_tones] {
+            lines.append(label);
+        }
+        widget.append(&lines);
+
+        Self {
+            widget,
+            emoji,
+            name,
+            keywords,
+            info,
+            skin_tones,
+            skin_tone_preference: skin_tones.clone(),
+            locales: locales.clone(),
+        }
+    }
+
+    /// Describe `emoji`, in the skin tone the grids show it in, or clear the preview.
+    pub fn show(&self, emoji: Option<&Emoji>) {
+        let Some(emoji) = emoji else {
+            for label in [&self.emoji, &self.name, &self.keywords, &self.info, &self.skin_tones] {
+                label.set_text("");
+            }
+            return;
+        };
+        let shown = self.skin_tone_preference.borrow().apply(emoji);
+        let details = Details::of(emoji, shown, &self.locales);
+        self.emoji.set_text(shown);
+        EmojiLabel::set_font_size(&self.emoji, if emoji.is_wide() { PREVIEW_TEXT_SIZE } else { PREVIEW_EMOJI_SIZE });
+        self.name.set_text(&details.name);
+        self.keywords.set_text(&details.keywords);
+        self.info.set_text(&details.info);
+        self.skin_tones.set_text(&details.skin_tones);
+        // Wide text is cut short in the preview too; the tooltip has all of it
+        self.emoji.set_tooltip_text(emoji.is_wide().then_some(shown));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::emoji_data::EMOJIS;
+
+    #[test]
+    fn test_details_of_a_toned_emoji() {
+        let thumbs_up = EMOJIS.iter().find(|e| e.ch == "👍").unwrap();
+        let details = Details::of(thumbs_up, "👍🏽", &["en"]);
+        assert_eq!(details.name, "thumbs up");
+        assert!(details.keywords.contains("+1"));
+        assert_eq!(details.info, format!("{} · U+1F44D U+1F3FD", thumbs_up.category));
+        assert_eq!(details.skin_tones.split(' ').count(), 5);
+
+        let grinning = EMOJIS.iter().find(|e| e.ch == "😀").unwrap();
+        assert!(Details::of(grinning, grinning.ch, &["en"]).skin_tones.is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+//! Geometry of the continuous browse layout (see [`crate::settings::BrowseLayout`]): all
+//! categories in one list of lines, each a header line followed by rows of its items. The
+//! items of a section may come in groups (the Unicode subgroups), each under a sub-header
+//! line of its own and starting on a new row.
+//!
+//! Every line has a fixed height, so the offset of a section and the section at a scroll
+//! position follow from the line counts, without asking the widgets.
+
+/// One line of the continuous grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Line {
+    /// The header of a section.
+    Header { section: usize },
+    /// The sub-header of the group of a section starting at item `start`.
+    Subheader { section: usize, start: usize },
+    /// Items `start..start + len` of a section.
+    Items { section: usize, start: usize, len: usize },
+}
+
+impl Line {
+    /// The section the line belongs to.
+    pub fn section(&self) -> usize {
+        match *self {
+            Line::Header { section } | Line::Subheader { section, .. } | Line::Items { section, .. } => section,
+        }
+    }
+}
+
+/// An item of the continuous grid: its section, and its index in that section.
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sections {
+    pub lines: Vec<Line>,
+    /// Number of items of each section.
+    lens: Vec<usize>,
+    /// Line of the header of each section.
+    headers: Vec<usize>,
+    /// Top of each line, followed by the total height.
+    tops: Vec<i32>,
+}
+
+impl Sections {
+    /// Lay out sections of `(group sizes, items per row)` under headers `header_height` high,
+    /// in rows `row_height` high. Sections of more than one group get a sub-header, as high
+    /// as a header, above each group.
+    pub fn new(sizes: &[(Vec<usize>, usize)], header_height: i32, row_height: i32) -> Self {
+        let mut lines = Vec::new();
+        let mut headers = Vec::with_capacity(sizes.len());
+        for (section, (groups, per_line)) in sizes.iter().enumerate() {
+            let per_line = (*per_line).max(1);
+            headers.push(lines.len());
+            lines.push(Line::Header { section });
+            let subheaders = groups.iter().filter(|&&len| len > 0).count() > 1;
+            let mut group_start = 0;
+            for &group_len in groups.iter().filter(|&&len| len > 0) {
+                if subheaders {
+                    lines.push(Line::Subheader { section, start: group_start });
+                }
+                for start in (0..group_len).step_by(per_line) {
+                    let len = per_line.min(group_len - start);
+                    lines.push(Line::Items { section, start: group_start + start, len });
+                }
+                group_start += group_len;
+            }
+        }
+        let mut tops = Vec::with_capacity(lines.len() + 1);
+        let mut top = 0;
+        for line in &lines {
+            tops.push(top);
+            top += match line {
+                Line::Header { .. } | Line::Subheader { .. } => header_height,
+                Line::Items { .. } => row_height,
+            };
+        }
+        tops.push(top);
+        let lens = sizes.iter().map(|(groups, _)| groups.iter().sum()).collect();
+        Self { lines, lens, headers, tops }
+    }
+
+    /// Height of all lines together.
+    pub fn height(&self) -> i32 {
+        self.tops[self.lines.len()]
+    }
+
+    /// Top of `line`.
+    pub fn line_top(&self, line: usize) -> i32 {
+        self.tops[line]
+    }
+
+    /// Height of `line`.
+    pub fn line_height(&self, line: usize) -> i32 {
+        self.tops[line + 1] - self.tops[line]
+    }
+
+    /// Top of the header of `section`.
+    pub fn section_top(&self, section: usize) -> i32 {
+        self.tops[self.headers[section]]
+    }
+
+    /// The section of the line at `y`, if there are any lines.
+    pub fn section_at(&self, y: f64) -> Option<usize> {
+        let starts = &self.tops[..self.lines.len()];
+        let line = starts.partition_point(|&top| top as f64 <= y.max(0.0)).checked_sub(1)?;
+        Some(self.lines[line].section())
+    }
+
+    /// The line showing `position`.
+    pub fn line_of(&self, (section, index): Position) -> usize {
+        let first = self.headers[section];
+        let end = self.headers.get(section + 1).copied().unwrap_or(self.lines.len());
+        // The lines of a section start at increasing items, so the last one starting at or
+        // before `index` shows it
+        let starting = self.lines[first..end].partition_point(|line| match *line {
+            Line::Header { .. } => true,
+            Line::Subheader { start, .. } | Line::Items { start, .. } => start <= index,
+        });
+        first + starting - 1
+    }
+
+    /// The first item, unless all sections are empty.
+    pub fn first(&self) -> Option<Position> {
+        self.first_of(0)
+    }
+
+    /// The first item of `section`, or of the first section after it that has any.
+    pub fn first_of(&self, section: usize) -> Option<Position> {
+        (section..self.lens.len()).find(|&s| self.lens[s] > 0).map(|s| (s, 0))
+    }
+
+    /// The item before `position`, going back into earlier sections; the very first item
+    /// stays where it is.
+    pub fn previous(&self, (section, index): Position) -> Position {
+        if index > 0 {
+            return (section, index - 1);
+        }
+        (0..section).rev().find(|&s| self.lens[s] > 0).map_or((section, index), |s| (s, self.lens[s] - 1))
+    }
+
+    /// The item after `position`, going on into later sections; the very last item stays
+    /// where it is.
+    pub fn next(&self, (section, index): Position) -> Position {
+        if index + 1 < self.lens[section] {
+            return (section, index + 1);
+        }
+        self.first_of(section + 1).unwrap_or((section, index))
+    }
+
+    /// The item in the same column on the row above (`up`) or below, across headers, or the
+    /// last one of a shorter row; `position` itself on the first or last row.
+    pub fn vertical(&self, position: Position, up: bool) -> Position {
+        let line = self.line_of(position);
+        let column = match self.lines[line] {
+            Line::Items { start, .. } => position.1 - start,
+            _ => 0,
+        };
+        let row_item = |line: &Line| match *line {
+            Line::Items { section, start, len } => Some((section, start + column.min(len - 1))),
+            Line::Header { .. } | Line::Subheader { .. } => None,
+        };
+        let found = if up {
+            self.lines[..line].iter().rev().find_map(row_item)
+        } else {
+            self.lines[line + 1..].iter().find_map(row_item)
+        };
+        found.unwrap_or(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_and_offsets() {
+        // Five items three per row, an empty section, and two items one per row
+        let sections = Sections::new(&[(vec![5], 3), (vec![], 3), (vec![2], 1)], 20, 40);
+        assert_eq!(
+            sections.lines,
+            vec![
+                Line::Header { section: 0 },
+                Line::Items { section: 0, start: 0, len: 3 },
+                Line::Items { section: 0, start: 3, len: 2 },
+                Line::Header { section: 1 },
+                Line::Header { section: 2 },
+                Line::Items { section: 2, start: 0, len: 1 },
+                Line::Items { section: 2, start: 1, len: 1 },
+            ]
+        );
+        assert_eq!(sections.height(), 220);
+        assert_eq!((sections.line_top(2), sections.line_height(2)), (60, 40));
+        assert_eq!(sections.section_top(2), 120);
+        assert_eq!(sections.section_at(-5.0), Some(0));
+        assert_eq!(sections.section_at(99.5), Some(0));
+        assert_eq!(sections.section_at(100.0), Some(1));
+        assert_eq!(sections.section_at(120.0), Some(2));
+        assert_eq!(sections.section_at(1000.0), Some(2));
+        assert_eq!(sections.line_of((0, 4)), 2);
+        assert_eq!(Sections::new(&[], 20, 40).section_at(0.0), None);
+    }
+
+    #[test]
+    fn test_moving_across_sections() {
+        let sections = Sections::new(&[(vec![5], 3), (vec![], 3), (vec![2], 1)], 20, 40);
+        assert_eq!(sections.first(), Some((0, 0)));
+        assert_eq!(sections.first_of(1), Some((2, 0)));
+        assert_eq!(sections.next((0, 4)), (2, 0));
+        assert_eq!(sections.next((2, 1)), (2, 1));
+        assert_eq!(sections.previous((2, 0)), (0, 4));
+        assert_eq!(sections.previous((0, 0)), (0, 0));
+        assert_eq!(sections.vertical((0, 4), true), (0, 1));
+        assert_eq!(sections.vertical((0, 1), false), (0, 4));
+        assert_eq!(sections.vertical((0, 2), false), (0, 4), "the last item of a shorter row");
+        assert_eq!(sections.vertical((0, 4), false), (2, 0), "across the empty section");
+        assert_eq!(sections.vertical((0, 1), true), (0, 1));
+        assert_eq!(sections.vertical((2, 1), false), (2, 1));
+    }
+
+    #[test]
+    fn test_groups_under_subheaders() {
+        // Groups of two and four items, three per row, and a section of a single group
+        let sections = Sections::new(&[(vec![2, 0, 4], 3), (vec![2], 3)], 20, 40);
+        assert_eq!(
+            sections.lines,
+            vec![
+                Line::Header { section: 0 },
+                Line::Subheader { section: 0, start: 0 },
+                Line::Items { section: 0, start: 0, len: 2 },
+                Line::Subheader { section: 0, start: 2 },
+                Line::Items { section: 0, start: 2, len: 3 },
+                Line::Items { section: 0, start: 5, len: 1 },
+                Line::Header { section: 1 },
+                Line::Items { section: 1, start: 0, len: 2 },
+            ]
+        );
+        assert_eq!(sections.height(), 20 * 4 + 40 * 4);
+        assert_eq!(sections.line_of((0, 1)), 2);
+        assert_eq!(sections.line_of((0, 2)), 4);
+        assert_eq!(sections.line_of((0, 5)), 5);
+        assert_eq!(sections.line_of((1, 1)), 7);
+        assert_eq!(sections.next((0, 1)), (0, 2));
+        assert_eq!(sections.vertical((0, 1), false), (0, 3), "the same column below the sub-header");
+        assert_eq!(sections.vertical((0, 4), true), (0, 1), "the last item of a shorter row");
+        assert_eq!(sections.vertical((0, 5), false), (1, 0));
+    }
+}
@@ -1,30 +1,141 @@
+use crate::paths;
+use crate::resources;
+use crate::settings::Theme;
+use gtk4::gdk::Display;
+use gtk4::gio;
 use gtk4::CssProvider;
+use libadwaita as adw;
 use std::fs;
+use std::io;
+use std::path::PathBuf;
 
-/// Loads and applies the emoji picker CSS from a static file.
+/// Base stylesheet, shared by all appearances.
+const BASE_STYLESHEET: &str = "style.css";
+/// Layer over the base stylesheet for light colors.
+const LIGHT_LAYER: &str = "style-light.css";
+/// Layer over the base stylesheet for dark colors.
+const DARK_LAYER: &str = "style-dark.css";
+/// Layer over the light or dark layer when high contrast is on.
+const HIGH_CONTRAST_LAYER: &str = "style-hc.css";
+
+/// Stylesheet in the config directory, layered over the bundled ones.
+pub const USER_STYLESHEET: &str = "style.css";
+
+/// The bundled stylesheet `name`, or `None` (logged) if it is missing.
+fn stylesheet(name: &str) -> Option<String> {
+    let path = format!("{}/css/{}", resources::BASE_PATH, name);
+    match gio::resources_lookup_data(&path, gio::ResourceLookupFlags::NONE) {
+        Ok(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+        Err(err) => {
+            log::warn!("Skipping stylesheet {}: {}", path, err);
+            None
+        }
+    }
+}
+
+/// Loads the emoji picker CSS bundled with the binary.
 ///
 /// All dynamic sizing (font-size, min-width, min-height) should be set directly on widgets in Rust.
 pub fn setup_css() -> CssProvider {
-    use std::env;
     let provider = CssProvider::new();
-    let mut tried = Vec::new();
-    let candidates = [
-        "data/style.css", // dev
-        "/app/share/emoji-picker/style.css", // Flatpak
-        // XDG data dir (Linux best practice)
-        &format!("{}/emoji-picker/style.css", env::var("XDG_DATA_HOME").unwrap_or_else(|_| String::from("~/.local/share"))),
-    ];
-    let css = candidates.iter().find_map(|path| {
-        tried.push(path.to_string());
-        fs::read_to_string(path).ok()
-    });
-    match css {
-        Some(css) => {
+    provider.load_from_data(&stylesheet(BASE_STYLESHEET).unwrap_or_default());
+    provider
+}
+
+/// Path of the user's own stylesheet, e.g. `~/.config/emoji-picker/style.css`.
+pub fn user_stylesheet_path() -> PathBuf {
+    paths::config_dir().join(USER_STYLESHEET)
+}
+
+/// Loads the user's stylesheet, if there is one.
+fn user_css() -> Option<CssProvider> {
+    let path = user_stylesheet_path();
+    match fs::read_to_string(&path) {
+        Ok(css) => {
+            log::info!("Using user stylesheet {}", path.display());
+            let provider = CssProvider::new();
             provider.load_from_data(&css);
-            provider
+            Some(provider)
         }
-        None => {
-            panic!("Failed to read CSS file. Tried: {}", tried.join(", "));
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => {
+            log::warn!("Could not read user stylesheet {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// The stylesheet layers for an appearance, bottom first.
+pub fn appearance_layers(dark: bool, high_contrast: bool) -> Vec<&'static str> {
+    let mut layers = vec![if dark { DARK_LAYER } else { LIGHT_LAYER }];
+    if high_contrast {
+        layers.push(HIGH_CONTRAST_LAYER);
+    }
+    layers
+}
+
+/// Load the layers for the current appearance of `style_manager` into `provider`.
+///
+/// A missing layer is skipped, leaving the base stylesheet and libadwaita's colors.
+fn load_appearance(provider: &CssProvider, style_manager: &adw::StyleManager) {
+    let layers = appearance_layers(style_manager.is_dark(), style_manager.is_high_contrast());
+    let css: Vec<String> = layers.iter().filter_map(|&name| stylesheet(name)).collect();
+    log::info!("Using stylesheet layers {:?}", layers);
+    provider.load_from_data(&css.join("\n"));
+}
+
+/// Style all windows on `display`: the base stylesheet, over it the layers for the current
+/// appearance, which are swapped whenever it turns light, dark or high contrast, and on top
+/// the user's stylesheet.
+pub fn install(display: &Display) {
+    gtk4::style_context_add_provider_for_display(display, &setup_css(), gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION);
+
+    let style_manager = adw::StyleManager::for_display(display);
+    let appearance = CssProvider::new();
+    load_appearance(&appearance, &style_manager);
+    gtk4::style_context_add_provider_for_display(
+        display,
+        &appearance,
+        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+    );
+    let appearance_clone = appearance.clone();
+    style_manager.connect_dark_notify(move |style_manager| load_appearance(&appearance_clone, style_manager));
+    style_manager.connect_high_contrast_notify(move |style_manager| load_appearance(&appearance, style_manager));
+
+    if let Some(user) = user_css() {
+        gtk4::style_context_add_provider_for_display(display, &user, gtk4::STYLE_PROVIDER_PRIORITY_USER);
+    }
+}
+
+/// Use the colors of `theme` in all windows.
+///
+/// [`Theme::System`] follows the system color scheme, and the light or dark layer follows
+/// along with it (see [`install`]).
+pub fn apply_theme(theme: Theme) {
+    let color_scheme = match theme {
+        Theme::System => adw::ColorScheme::Default,
+        Theme::Light => adw::ColorScheme::ForceLight,
+        Theme::Dark => adw::ColorScheme::ForceDark,
+    };
+    adw::StyleManager::default().set_color_scheme(color_scheme);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_high_contrast_layers_over_light_or_dark() {
+        assert_eq!(appearance_layers(false, false), vec![LIGHT_LAYER]);
+        assert_eq!(appearance_layers(true, false), vec![DARK_LAYER]);
+        assert_eq!(appearance_layers(true, true), vec![DARK_LAYER, HIGH_CONTRAST_LAYER]);
+    }
+
+    #[test]
+    fn test_stylesheets_are_bundled() {
+        resources::register().unwrap();
+        for name in [BASE_STYLESHEET, LIGHT_LAYER, DARK_LAYER, HIGH_CONTRAST_LAYER] {
+            assert!(stylesheet(name).is_some_and(|css| !css.is_empty()), "{} is missing", name);
         }
     }
 }
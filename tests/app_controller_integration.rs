@@ -21,7 +21,7 @@ fn test_search_changes_mode_and_filters() {
     let controller = make_controller();
     controller.borrow_mut().handle_search("smile");
     assert_eq!(controller.borrow().mode, PickerMode::Search);
-    assert!(controller.borrow().filtered_emojis.iter().any(|e| e.name_en.contains("smile")));
+    assert!(controller.borrow().filtered_emojis.iter().any(|e| e.name(&["en"]).contains("smile")));
 }
 
 #[test]
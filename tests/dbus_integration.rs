@@ -0,0 +1,161 @@
+//! Integration tests for the D-Bus interface, against a private session bus.
+//!
+//! Each test starts its own `dbus-daemon` and is skipped when that program is not installed.
+
+use emoji_picker::dbus;
+use emoji_picker::emoji::emoji_data::{EMOJIS, LOCALES};
+use emoji_picker::ui::app_controller::EmojiPickerController;
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A private bus with the picker exported on one connection and a client on another.
+struct Fixture {
+    bus: Child,
+    context: glib::MainContext,
+    service: gio::DBusConnection,
+    client: gio::DBusConnection,
+    controller: Rc<RefCell<EmojiPickerController>>,
+    shown: Rc<RefCell<Vec<String>>>,
+}
+
+impl Fixture {
+    fn call(&self, method: &str, argument: &str) -> Result<glib::Variant, glib::Error> {
+        let service_name = self.service.unique_name().expect("bus connection has a unique name");
+        let reply = self.client.call_future(
+            Some(&service_name),
+            dbus::OBJECT_PATH,
+            dbus::INTERFACE_NAME,
+            method,
+            Some(&(argument,).to_variant()),
+            None,
+            gio::DBusCallFlags::NONE,
+            5000,
+        );
+        self.context.block_on(reply)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = self.bus.kill();
+        let _ = self.bus.wait();
+    }
+}
+
+fn dbus_daemon_available() -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join("dbus-daemon").is_file()))
+        .unwrap_or(false)
+}
+
+/// Run `test` against a fresh private bus, or skip it without `dbus-daemon`.
+fn with_private_bus(test: impl FnOnce(&Fixture)) {
+    if !dbus_daemon_available() {
+        eprintln!("dbus-daemon not found, skipping D-Bus test");
+        return;
+    }
+    let mut bus = Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--print-address"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("start dbus-daemon");
+    let mut address = String::new();
+    BufReader::new(bus.stdout.take().unwrap()).read_line(&mut address).expect("read bus address");
+    let address = address.trim().to_string();
+    let context = glib::MainContext::new();
+    context
+        .with_thread_default(|| {
+            let flags =
+                gio::DBusConnectionFlags::AUTHENTICATION_CLIENT | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION;
+            let connect = || {
+                gio::DBusConnection::for_address_sync(&address, flags, None, None::<&gio::Cancellable>)
+                    .expect("connect to private bus")
+            };
+            let mut controller = EmojiPickerController::new(EMOJIS.to_vec());
+            controller.locales = LOCALES.to_vec();
+            let controller = Rc::new(RefCell::new(controller));
+            let shown = Rc::new(RefCell::new(Vec::new()));
+            let service = connect();
+            let shown_clone = shown.clone();
+            dbus::export(&service, controller.clone(), move |query| shown_clone.borrow_mut().push(query.to_string()))
+                .expect("export picker interface");
+            let fixture = Fixture { bus, context: context.clone(), service, client: connect(), controller, shown };
+            test(&fixture);
+        })
+        .expect("acquire test main context");
+}
+
+#[test]
+fn test_search_returns_ranked_emoji_with_names() {
+    with_private_bus(|fixture| {
+        let reply = fixture.call("Search", "tada").unwrap();
+        let (results,) = reply.get::<(Vec<(String, String)>,)>().unwrap();
+        assert_eq!(results[0].0, "🎉");
+        assert!(results[0].1.contains("party popper"));
+    });
+}
+
+#[test]
+fn test_lookup_returns_metadata() {
+    with_private_bus(|fixture| {
+        let reply = fixture.call("Lookup", "👍🏽").unwrap();
+        let metadata = glib::VariantDict::new(Some(&reply.child_value(0)));
+        let lookup = |key| metadata.lookup::<String>(key).unwrap().unwrap();
+        assert_eq!(lookup("emoji"), "👍");
+        assert_eq!(lookup("category"), "People & Body");
+        let variants = metadata.lookup::<Vec<String>>("skin-tone-variants").unwrap().unwrap();
+        assert!(variants.contains(&"👍🏽".to_string()));
+    });
+}
+
+#[test]
+fn test_lookup_of_unknown_text_fails() {
+    with_private_bus(|fixture| {
+        let err = fixture.call("Lookup", "not an emoji").unwrap_err();
+        assert!(err.message().contains(dbus::NOT_FOUND_ERROR), "{}", err);
+    });
+}
+
+#[test]
+fn test_show_passes_the_query_on() {
+    with_private_bus(|fixture| {
+        fixture.call("Show", "cat").unwrap();
+        assert_eq!(*fixture.shown.borrow(), vec!["cat"]);
+    });
+}
+
+#[test]
+fn test_picks_are_signalled() {
+    with_private_bus(|fixture| {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_clone = received.clone();
+        let service_name = fixture.service.unique_name().unwrap();
+        let _subscription = fixture.client.signal_subscribe(
+            Some(&service_name),
+            Some(dbus::INTERFACE_NAME),
+            Some("Picked"),
+            Some(dbus::OBJECT_PATH),
+            None,
+            gio::DBusSignalFlags::NONE,
+            move |_, _, _, _, _, parameters| {
+                received_clone.borrow_mut().push(parameters.get::<(String,)>().unwrap().0);
+            },
+        );
+        // A round trip makes sure the bus has seen the subscription before the signal is sent
+        fixture.call("Search", "cat").unwrap();
+        fixture.controller.borrow_mut().record_pick("👍🏽");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received.borrow().is_empty() && Instant::now() < deadline {
+            if !fixture.context.iteration(false) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+        assert_eq!(*received.borrow(), vec!["👍🏽"]);
+    });
+}
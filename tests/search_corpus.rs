@@ -0,0 +1,109 @@
+//! Search quality corpus: each query must rank the expected emoji first in the generated EMOJIS table,
+//! or among the first few for queries with many fair answers.
+
+use emoji_picker::emoji::emoji_data::EMOJIS;
+use emoji_picker::emoji::search::{rank, rank_indexed, search_with_usage};
+use emoji_picker::emoji::usage::UsageHistory;
+use std::collections::HashSet;
+
+/// (query, expected top result), searched in English
+const CORPUS: &[(&str, &str)] = &[
+    // Exact names and keywords
+    ("pizza", "🍕"),
+    ("rocket", "🚀"),
+    ("unicorn", "🦄"),
+    ("dog", "🐕"),
+    ("dog face", "🐶"),
+    ("thumbs up", "👍"),
+    ("red heart", "❤️"),
+    ("party popper", "🎉"),
+    ("tada", "🎉"),
+    // Words in any order
+    ("heart red", "❤️"),
+    ("up thumbs", "👍"),
+    ("popper party", "🎉"),
+    // Typos
+    ("smilng", "😄"),
+    ("unicron", "🦄"),
+    ("raibow", "🌈"),
+    ("scisors", "✂️"),
+    ("gost", "👻"),
+    ("ghots", "👻"),
+    ("firefigther", "🧑‍🚒"),
+    ("hot bevrage", "☕"),
+    // Subsequences
+    ("thmbup", "👍"),
+    ("thmbsdn", "👎"),
+    ("rdhrt", "❤️"),
+    ("prtypop", "🎉"),
+    ("crsdfngrs", "🤞"),
+    ("unicrn", "🦄"),
+];
+
+/// (query, expected top result), searched in Dutch with English as fallback
+const CORPUS_NL: &[(&str, &str)] = &[
+    ("koffie", "☕"),
+    ("bier", "🍺"),
+    ("kat", "🐈"),
+    ("rood hart", "❤️"),
+];
+
+/// (query, emoji expected among the first `TOP_N` results), searched in English
+const CORPUS_TOP_N: &[(&str, &str)] = &[
+    ("smiel", "😄"),
+    ("smiel", "😀"),
+    ("grining", "😀"),
+    ("smiling face", "☺️"),
+];
+
+const TOP_N: usize = 5;
+
+/// Queries that only match after a hyphen, such as the subsequence "eed" in "bright-eyed"
+const HYPHENATED: &[&str] = &["x-ray", "t-rex", "rex", "eed", "brght eyd", "pinted star"];
+
+#[test]
+fn test_search_corpus_top_results() {
+    let english = CORPUS.iter().map(|entry| (entry, &["en"][..]));
+    let dutch = CORPUS_NL.iter().map(|entry| (entry, &["nl", "en"][..]));
+    let failures: Vec<String> = english
+        .chain(dutch)
+        .filter_map(|(&(query, expected), locales)| {
+            let results = search_with_usage(query, locales, &UsageHistory::default(), 0);
+            let top = results.first().map(|e| e.ch).unwrap_or("<none>");
+            (top != expected).then(|| format!("{:?} in {:?}: expected {}, got {}", query, locales, expected, top))
+        })
+        .collect();
+    assert!(failures.is_empty(), "search corpus mismatches:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_search_corpus_top_n_results() {
+    let failures: Vec<String> = CORPUS_TOP_N
+        .iter()
+        .filter_map(|&(query, expected)| {
+            let results = search_with_usage(query, &["en"], &UsageHistory::default(), 0);
+            let top: Vec<&str> = results.iter().take(TOP_N).map(|e| e.ch).collect();
+            (!top.contains(&expected)).then(|| format!("{:?}: expected {} in {}", query, expected, top.join(" ")))
+        })
+        .collect();
+    assert!(failures.is_empty(), "search corpus mismatches:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_indexed_search_finds_the_same_emoji_as_a_full_scan() {
+    let queries = CORPUS.iter().chain(CORPUS_NL).chain(CORPUS_TOP_N).map(|&(query, _)| query).chain(HYPHENATED.iter().copied());
+    let usage = UsageHistory::default();
+    let mut mismatches = Vec::new();
+    for query in queries {
+        for locales in [&["en"][..], &["nl", "en"][..]] {
+            let scan: HashSet<&str> = rank(EMOJIS, query, locales, &usage, 0).iter().map(|e| e.ch).collect();
+            let indexed: HashSet<&str> = rank_indexed(query, locales, &usage, 0).iter().map(|e| e.ch).collect();
+            if scan != indexed {
+                let missing: Vec<&str> = scan.difference(&indexed).copied().collect();
+                let extra: Vec<&str> = indexed.difference(&scan).copied().collect();
+                mismatches.push(format!("{:?} in {:?}: missing {:?}, extra {:?}", query, locales, missing, extra));
+            }
+        }
+    }
+    assert!(mismatches.is_empty(), "indexed search differs from the full scan:\n{}", mismatches.join("\n"));
+}